@@ -0,0 +1,82 @@
+use hobo::{prelude::*, create as e};
+use super::element_ext::AsElementExt;
+
+/// How urgently screen readers should read out an announcement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Politeness {
+	/// Once the user is idle.
+	#[default] Polite,
+	/// Interrupting whatever is being read.
+	Assertive,
+}
+
+// present in the accessibility tree, but not visible
+fn visually_hidden() -> Vec<css::Property> {
+	css::properties![
+		css::position::absolute,
+		css::width::px(1),
+		css::height::px(1),
+		css::margin::px(-1),
+		css::padding_top::zero,
+		css::padding_right::zero,
+		css::padding_bottom::zero,
+		css::padding_left::zero,
+		css::overflow::hidden,
+		// hobo_css has no `clip`
+		css::Property::Raw("clip:rect(0 0 0 0);".to_owned()),
+		css::white_space::nowrap,
+		css::border_width::zero,
+	]
+}
+
+thread_local! {
+	// live regions have to exist before their content changes for the change to be announced, so these are made upfront
+	static REGIONS: Option<(e::Div, e::Div)> = super::document().body().map(|body| {
+		struct LiveRegionTag;
+		let region = |politeness: &str| e::div()
+			.attr("aria-live", politeness)
+			.attr("aria-atomic", "true")
+			.class_typed::<LiveRegionTag>(visually_hidden())
+			.allow_no_parent();
+		let regions = (region("polite"), region("assertive"));
+		body.append_child(&regions.0.get_cmp::<web_sys::Node>()).ok();
+		body.append_child(&regions.1.get_cmp::<web_sys::Node>()).ok();
+		regions
+	});
+}
+
+/// Has screen readers read out `message` via an `aria-live` region, without showing anything.
+pub fn announce(message: &str, politeness: Politeness) {
+	let Some((polite, assertive)) = REGIONS.with(|x| *x) else { log::warn!("can't announce without a body"); return; };
+	let region = match politeness {
+		Politeness::Polite => polite,
+		Politeness::Assertive => assertive,
+	};
+	// clearing first so repeating the same message is announced again
+	region.set_text("");
+	let message = message.to_owned();
+	region.set_on_next_flow(move || region.set_text(message));
+}
+
+/// What `announce_route_changes` moves focus to, the first match wins.
+pub const MAIN_HEADING_SELECTOR: &str = "main h1, h1";
+
+/// Makes client-side navigation accessible the way full page loads are - on each change of `route` (after the initial one),
+/// politely announces `title_fn(route)` and moves focus to the main heading of the new page.
+///
+/// There's no router in this crate, feed in the current route from whatever the app uses.
+/// The heading is looked up after the next flow so the new page has a chance to render, and made focusable with `tabindex=-1` if it isn't.
+/// Announcements stop when the returned subscription is dropped.
+pub fn announce_route_changes(route: impl hobo::signal::Signal<Item = String> + 'static, title_fn: impl Fn(&str) -> String + 'static) -> hobo::signals_ext::Subscription {
+	let mut initial = true;
+	route.subscribe(move |route| {
+		if std::mem::take(&mut initial) { return; }
+		announce(&title_fn(&route), Politeness::Polite);
+		super::window().request_animation_frame(Closure::once_into_js(|| {
+			let Some(heading) = super::document().query_selector(MAIN_HEADING_SELECTOR).ok().flatten() else { return; };
+			let Ok(heading) = heading.dyn_into::<web_sys::HtmlElement>() else { return; };
+			if !heading.has_attribute(web_str::tabindex()) { heading.set_attribute(web_str::tabindex(), "-1").ok(); }
+			heading.focus().ok();
+		}).unchecked_ref()).ok();
+	})
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+/// Linearly growing delay between retries, e.g. reconnecting or re-sending.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Backoff {
+	step: Duration,
+	max: Duration,
+	current: Duration,
+}
+
+impl Backoff {
+	/// Each retry waits `step` longer than the previous one, up to `max`.
+	pub fn new(step: Duration, max: Duration) -> Self { Self { step, max, current: Duration::ZERO } }
+
+	/// The delay before the next retry.
+	pub fn next_delay(&mut self) -> Duration {
+		self.current = (self.current + self.step).min(self.max);
+		self.current
+	}
+
+	/// Start over after a success.
+	pub fn reset(&mut self) { self.current = Duration::ZERO; }
+
+	pub async fn wait(&mut self) { async_timer::new_timer(self.next_delay()).await; }
+}
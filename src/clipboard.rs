@@ -0,0 +1,94 @@
+use hobo::prelude::*;
+use wasm_bindgen_futures::{js_sys, JsFuture};
+use super::window;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardError {
+	#[error("Clipboard is not available.")] Unsupported,
+	#[error("Clipboard write failed: '{0}'.")] WriteFailed(String),
+}
+
+/// One representation of the copied content, several of them make up a single clipboard entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClipboardItem {
+	Text(String),
+	Html(String),
+	/// E.g. a canvas or `element_to_png` blob, the mime type is taken from the blob and defaults to `image/png`.
+	Image(web_sys::Blob),
+}
+
+fn clipboard() -> Result<web_sys::Clipboard, ClipboardError> {
+	let navigator = window().navigator();
+	if !js_sys::Reflect::has(&navigator, &"clipboard".into()).unwrap_or(false) { return Err(ClipboardError::Unsupported); }
+	Ok(navigator.clipboard())
+}
+
+async fn write_promise(promise: js_sys::Promise) -> Result<(), ClipboardError> {
+	JsFuture::from(promise).await
+		.map(|_| ())
+		.map_err(|e| ClipboardError::WriteFailed(e.as_string().unwrap_or_else(|| format!("{e:?}"))))
+}
+
+pub async fn write_text(text: &str) -> Result<(), ClipboardError> {
+	write_promise(clipboard()?.write_text(text)).await
+}
+
+fn text_blob(text: &str, mime: &str) -> Result<web_sys::Blob, ClipboardError> {
+	let options = web_sys::BlobPropertyBag::new();
+	options.set_type(mime);
+	web_sys::Blob::new_with_str_sequence_and_options(&js_sys::Array::of1(&text.into()), &options)
+		.map_err(|e| ClipboardError::WriteFailed(format!("{e:?}")))
+}
+
+/// Writes all the `items` as alternative representations of a single clipboard entry - e.g. html for rich text editors and plain text for everything else.
+///
+/// Where rich clipboard writes aren't supported (or get rejected), falls back to writing just the `Text` item, if there is one.
+pub async fn write_items(items: Vec<ClipboardItem>) -> Result<(), ClipboardError> {
+	let clipboard = clipboard()?;
+	let fallback_text = items.iter().find_map(|x| if let ClipboardItem::Text(x) = x { Some(x.clone()) } else { None });
+
+	let rich = async {
+		if !js_sys::Reflect::has(&window(), &"ClipboardItem".into()).unwrap_or(false) { return Err(ClipboardError::Unsupported); }
+		let record = js_sys::Object::new();
+		for item in &items {
+			let (mime, blob) = match item {
+				ClipboardItem::Text(x) => ("text/plain".to_owned(), text_blob(x, "text/plain")?),
+				ClipboardItem::Html(x) => ("text/html".to_owned(), text_blob(x, "text/html")?),
+				ClipboardItem::Image(x) => (Some(x.type_()).filter(|x| !x.is_empty()).unwrap_or_else(|| "image/png".to_owned()), x.clone()),
+			};
+			js_sys::Reflect::set(&record, &mime.into(), &blob).ok();
+		}
+		let item = web_sys::ClipboardItem::new_with_record_from_str_to_blob_promise(&record)
+			.map_err(|e| ClipboardError::WriteFailed(format!("{e:?}")))?;
+		write_promise(clipboard.write(&js_sys::Array::of1(&item))).await
+	};
+
+	match (rich.await, fallback_text) {
+		(Ok(()), _) => Ok(()),
+		(Err(e), Some(text)) => {
+			log::warn!("rich clipboard write failed, falling back to text: {e}");
+			write_text(&text).await
+		},
+		(Err(e), None) => Err(e),
+	}
+}
+
+/// Replaces what a `copy`/`cut` event puts on the clipboard with `entries` of `(mime, data)` - e.g. a custom mime type for pasting within the app
+/// plus `text/plain` for everywhere else. Use from hobo's `on_copy`/`on_cut`.
+///
+/// Prevents the default action, so for `cut` removing the selected content is up to the caller.
+/// Returns `false` without preventing anything if the event has no clipboard data to write to.
+pub fn set_event_data(e: &web_sys::ClipboardEvent, entries: &[(&str, &str)]) -> bool {
+	let Some(data_transfer) = e.clipboard_data() else { return false; };
+	data_transfer.clear_data().ok();
+	for (mime, data) in entries {
+		if let Err(e) = data_transfer.set_data(mime, data) { log::warn!("failed to set clipboard data: {e:?}"); }
+	}
+	e.prevent_default();
+	true
+}
+
+/// The `mime` representation of what's being pasted in a `paste` event, if there is one.
+pub fn event_data(e: &web_sys::ClipboardEvent, mime: &str) -> Option<String> {
+	e.clipboard_data()?.get_data(mime).ok().filter(|x| !x.is_empty())
+}
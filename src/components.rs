@@ -0,0 +1,31 @@
+use std::{cell::Cell, rc::Rc};
+use hobo::prelude::*;
+use super::{entity_ext::AsEntityExt, listener::listen, observer::observe_intersection};
+
+/// Renders a cheap `placeholder` and only builds the real (heavy) element the first time the user interacts with the placeholder
+/// (hovers, focuses, presses or touches it) or it becomes visible, at which point the built element replaces the placeholder.
+///
+/// All the triggers live on the placeholder, so they are all cancelled once it gets replaced.
+/// The returned element is the placeholder, so don't hold onto it past the replacement.
+pub fn defer_until_interaction<E: AsElement + 'static>(placeholder: impl AsElement, build: impl FnOnce() -> E + 'static) -> hobo::Element {
+	let placeholder = placeholder.as_element();
+	let build = Cell::new(Some(build));
+	let trigger = Rc::new(move || {
+		let Some(build) = build.take() else { return; };
+		// replacing removes the placeholder along with the listener that's currently running, so do it after it returns
+		wasm_bindgen_futures::spawn_local(async move {
+			if placeholder.is_dead() { return; }
+			placeholder.replace_with(build().as_element());
+		});
+	});
+
+	let target = placeholder.get_cmp::<web_sys::EventTarget>().clone();
+	for event in ["mouseenter", "focusin", "pointerdown", "touchstart"] {
+		placeholder.add_bundle(listen(&target, event, #[clown::clown] move |_: web_sys::Event| honk!(trigger)()));
+	}
+	placeholder.add_bundle(observe_intersection([&*placeholder.get_cmp::<web_sys::Element>()], &web_sys::IntersectionObserverInit::new(), move |entries| {
+		if entries.iter().any(web_sys::IntersectionObserverEntry::is_intersecting) { trigger(); }
+	}));
+
+	placeholder
+}
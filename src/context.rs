@@ -0,0 +1,34 @@
+use hobo::prelude::*;
+
+/// The window and document that document-level helpers listen on and measure the viewport of.
+///
+/// Defaults to the global ones, which is wrong for elements that end up in an iframe or another window -
+/// pass the right one to the `_in` variants of helpers for those.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Context {
+	pub window: web_sys::Window,
+	pub document: web_sys::Document,
+}
+
+impl Context {
+	pub fn global() -> Self { Self { window: super::window(), document: super::document() } }
+
+	pub fn from_window(window: web_sys::Window) -> Self {
+		let document = window.document().expect("no document");
+		Self { window, document }
+	}
+
+	/// The context `element` currently lives in - its owner document and that document's window.
+	///
+	/// Elements are owned by the document that created them until they're inserted into another one,
+	/// so this is only meaningful once the element is in place. Falls back to the global context.
+	pub fn of(element: &impl AsElement) -> Self {
+		element.get_cmp::<web_sys::Node>().owner_document()
+			.and_then(|document| Some(Self { window: document.default_view()?, document }))
+			.unwrap_or_else(Self::global)
+	}
+}
+
+impl Default for Context {
+	fn default() -> Self { Self::global() }
+}
@@ -0,0 +1,96 @@
+use hobo::prelude::*;
+use super::{document, window};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfettiOptions {
+	/// Any css colors, each particle picks one at random.
+	pub colors: Vec<String>,
+	pub count: usize,
+	/// Angle of the cone (in degrees, pointing up) the particles are launched in.
+	pub spread: f64,
+	/// Particles fade out towards the end, the effect is over after this many milliseconds at the latest.
+	pub duration_ms: f64,
+}
+
+impl Default for ConfettiOptions {
+	fn default() -> Self {
+		Self {
+			colors: ["#f94144", "#f8961e", "#f9c74f", "#90be6d", "#43aa8b", "#577590"].map(String::from).to_vec(),
+			count: 120,
+			spread: 70.,
+			duration_ms: 3000.,
+		}
+	}
+}
+
+struct Particle {
+	x: f64, y: f64,
+	vx: f64, vy: f64,
+	rotation: f64, angular_velocity: f64,
+	width: f64, height: f64,
+	color: usize,
+}
+
+const GRAVITY: f64 = 1800.;
+const DRAG: f64 = 0.9;
+
+fn random_range(min: f64, max: f64) -> f64 { min + js_sys::Math::random() * (max - min) }
+
+/// Bursts confetti out of `origin` (viewport coordinates).
+///
+/// Draws on a short-lived fullscreen canvas overlay that doesn't capture pointer events and is removed once all particles have settled.
+pub fn confetti(origin: (f64, f64), options: ConfettiOptions) {
+	let document = document();
+	let Some(body) = document.body() else { return; };
+	if options.count == 0 || options.colors.is_empty() { return; }
+
+	let canvas: web_sys::HtmlCanvasElement = document.create_element("canvas").unwrap().unchecked_into();
+	canvas.set_attribute(web_str::style(), "position: fixed; inset: 0; width: 100vw; height: 100vh; pointer-events: none; z-index: 2147483647;").unwrap();
+	let pixel_ratio = window().device_pixel_ratio();
+	let (width, height) = (window().inner_width().unwrap().as_f64().unwrap(), window().inner_height().unwrap().as_f64().unwrap());
+	canvas.set_width((width * pixel_ratio) as u32);
+	canvas.set_height((height * pixel_ratio) as u32);
+	body.append_child(&canvas).unwrap();
+	let ctx: web_sys::CanvasRenderingContext2d = canvas.get_context("2d").unwrap().unwrap().unchecked_into();
+	ctx.scale(pixel_ratio, pixel_ratio).ok();
+
+	let mut particles = (0..options.count).map(|_| {
+		let angle = (-90. + random_range(-options.spread, options.spread) / 2.).to_radians();
+		let speed = random_range(600., 1200.);
+		Particle {
+			x: origin.0, y: origin.1,
+			vx: angle.cos() * speed, vy: angle.sin() * speed,
+			rotation: random_range(0., std::f64::consts::TAU), angular_velocity: random_range(-10., 10.),
+			width: random_range(6., 10.), height: random_range(3., 6.),
+			color: (js_sys::Math::random() * options.colors.len() as f64) as usize % options.colors.len(),
+		}
+	}).collect::<Vec<_>>();
+
+	let mut elapsed = 0.;
+	super::animation(move |delta_t| {
+		elapsed += delta_t;
+		let dt = delta_t / 1000.;
+		particles.retain_mut(|p| {
+			p.vx *= DRAG.powf(dt);
+			p.vy = p.vy * DRAG.powf(dt) + GRAVITY * dt;
+			p.x += p.vx * dt;
+			p.y += p.vy * dt;
+			p.rotation += p.angular_velocity * dt;
+			p.y < height + p.width
+		});
+
+		if particles.is_empty() || elapsed >= options.duration_ms { canvas.remove(); return false; }
+
+		ctx.clear_rect(0., 0., width, height);
+		ctx.set_global_alpha(f64::min(1., (options.duration_ms - elapsed) / (options.duration_ms * 0.25)));
+		for p in &particles {
+			ctx.save();
+			ctx.translate(p.x, p.y).ok();
+			ctx.rotate(p.rotation).ok();
+			ctx.set_fill_style_str(&options.colors[p.color]);
+			ctx.fill_rect(-p.width / 2., -p.height / 2., p.width, p.height);
+			ctx.restore();
+		}
+		true
+	});
+}
@@ -1,8 +1,17 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
 use hobo::{prelude::*, signal::SignalExt};
-use super::{window, closure_mut};
+#[allow(unused_imports)] use super::{honk, slip};
+use super::{window, closure_mut, request_frame_coalesced, AnimationHandle, Context};
 use super::entity_ext::AsEntityExt;
+use super::focus::tabbable_elements;
+use super::listener::{listen, listen_capture};
+use super::observer::{observe_intersection, observe_mutations, observe_resize, ObserverHandle};
+use super::shortcuts::{KeyCombo, Shortcut};
+use super::spring::{SpringConfig, SpringValue};
 
 pub mod children_diff;
+mod computed_style;
+mod to_png;
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub struct FontTag;
@@ -11,6 +20,311 @@ pub struct FontTag;
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Clicked(pub bool);
 
+/// Configuration for `autoscroll_on_drag_edge`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EdgeScrollConfig {
+	/// How close to the container's edge (in px) the pointer has to be for scrolling to kick in.
+	pub zone_px: f64,
+	/// Scrolling speed in px per second when the pointer is right at the edge, it falls off linearly towards the inner end of the zone.
+	pub speed: f64,
+}
+
+/// Unit for the values fed into `length_signal`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+	Px,
+	Rem,
+	Percent,
+}
+
+impl LengthUnit {
+	pub fn unit(self, x: f64) -> css::Unit {
+		match self {
+			Self::Px => css::Unit::px(x),
+			Self::Rem => css::Unit::rem(x),
+			Self::Percent => css::Unit::pct(x),
+		}
+	}
+}
+
+/// Whether and how text in an element can be selected, for `user_select`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UserSelect {
+	#[default] Auto,
+	None,
+	Text,
+	/// A single click selects the whole element.
+	All,
+}
+
+/// Entrance animation for `reveal_on_scroll`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RevealKind {
+	FadeIn,
+	FadeUp,
+	ScaleIn,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RevealConfig {
+	pub animation: RevealKind,
+	/// Only reveal the first time, otherwise the element gets hidden again when it leaves the viewport so it can be revealed again.
+	pub once: bool,
+}
+
+fn reveal_properties(kind: RevealKind, revealed: bool) -> Vec<css::Property> {
+	let hidden_transform = match kind {
+		RevealKind::FadeIn => "none",
+		RevealKind::FadeUp => "translateY(24px)",
+		RevealKind::ScaleIn => "scale(0.95)",
+	};
+	let (opacity, transform) = if revealed { ("1", "none") } else { ("0", hidden_transform) };
+	vec![
+		css::Property::Raw(format!("opacity:{opacity};")),
+		css::Property::Raw(format!("transform:{transform};")),
+		css::Property::Raw("transition:opacity 600ms ease-out, transform 600ms ease-out;".to_owned()),
+	]
+}
+
+/// What made `dismissable` dismiss an element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DismissReason {
+	/// A pointer was pressed outside of the element.
+	OutsideClick,
+	Escape,
+	/// Focus moved from within the element to something outside of it.
+	FocusLost,
+}
+
+/// Which CSS animation event `on_animation_event` reacts to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnimEventKind {
+	Start,
+	Iteration,
+	End,
+}
+
+impl AnimEventKind {
+	fn event_name(self) -> &'static str {
+		match self {
+			Self::Start => "animationstart",
+			Self::Iteration => "animationiteration",
+			Self::End => "animationend",
+		}
+	}
+}
+
+/// A single step of zooming reported by `on_zoom`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZoomInfo {
+	/// Relative to the previous step, e.g. `1.1` means 10% bigger.
+	pub scale: f64,
+	/// Element-local coordinates of the point that should stay in place.
+	pub focal_x: f64,
+	pub focal_y: f64,
+}
+
+// how much a px of ctrl+wheel delta zooms, tuned so a mouse wheel notch is roughly 10%
+const WHEEL_ZOOM_SPEED: f64 = 0.001;
+
+fn touch_pair(e: &web_sys::TouchEvent) -> Option<((f64, f64), f64)> {
+	let touches = e.touches();
+	if touches.length() != 2 { return None; }
+	let (a, b) = (touches.get(0)?, touches.get(1)?);
+	let (ax, ay, bx, by) = (a.client_x() as f64, a.client_y() as f64, b.client_x() as f64, b.client_y() as f64);
+	Some((((ax + bx) / 2., (ay + by) / 2.), f64::hypot(ax - bx, ay - by)))
+}
+
+/// An element's inline style as saved by `snapshot_style`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StyleSnapshot(Option<String>);
+
+/// Longest `AsElementExt::flash` lasts when the user prefers reduced motion.
+pub const FLASH_REDUCED_MOTION_MS: f64 = 500.;
+
+/// How long `AsElementExt::confirm_action` waits for the confirming click.
+pub const CONFIRM_ACTION_TIMEOUT_MS: u32 = 3000;
+
+// `fit_text` stops narrowing down the font size once it's within this
+const FIT_TEXT_PRECISION_PX: f64 = 0.5;
+const FLASH_OUTLINE_PX: f64 = 3.;
+
+// the inline style from before the first of overlapping `flash`es, and which of them is the latest
+struct Flashing {
+	snapshot: StyleSnapshot,
+	generation: u64,
+}
+
+/// Size changes up to this many px are ignored by `size_signal`.
+pub const SIZE_EPSILON_PX: f64 = 0.5;
+
+/// Granularity of `AsElementExt::intersection_ratio_signal`.
+pub const INTERSECTION_RATIO_STEP: f64 = 0.01;
+
+/// How far outside the viewport `AsElementExt::lazy_background` starts loading, as an `IntersectionObserver` root margin.
+pub const LAZY_BACKGROUND_MARGIN: &str = "200px";
+
+/// How far, as a fraction of its width, an element has to be dragged for `AsElementExt::swipe_to_dismiss` to dismiss it on release.
+pub const SWIPE_DISMISS_FRACTION: f64 = 0.4;
+
+/// Release speed in px per second past which `AsElementExt::swipe_to_dismiss` dismisses regardless of the distance, i.e. a fling.
+pub const SWIPE_FLING_VELOCITY: f64 = 1000.;
+
+// where `swipe_to_dismiss` is in a drag, velocity in px per second
+#[derive(Clone, Copy)]
+struct SwipeDrag {
+	pointer_id: i32,
+	start_x: f64,
+	last_x: f64,
+	last_time: f64,
+	velocity: f64,
+}
+
+// marks the `<mark>`s made by `highlight_matches`, so they can be told apart from ones that were there already
+const HIGHLIGHT_ATTR: &str = "data-hobo-highlight";
+
+fn clear_highlights(element: &web_sys::Element) {
+	let Ok(marks) = element.query_selector_all(&format!("mark[{HIGHLIGHT_ATTR}]")) else { return; };
+	for mark in (0..marks.length()).filter_map(|i| marks.item(i)) {
+		let Some(parent) = mark.parent_node() else { continue; };
+		let text = super::document().create_text_node(&mark.text_content().unwrap_or_default());
+		parent.replace_child(&text, &mark).ok();
+		parent.normalize();
+	}
+}
+
+fn collect_text_nodes(node: &web_sys::Node, out: &mut Vec<web_sys::Text>) {
+	let children = node.child_nodes();
+	for child in (0..children.length()).filter_map(|i| children.item(i)) {
+		match child.dyn_into::<web_sys::Text>() {
+			Ok(text) => out.push(text),
+			Err(child) => collect_text_nodes(&child, out),
+		}
+	}
+}
+
+// everything that affects where the text of an input/textarea ends up
+const CARET_MIRROR_PROPERTIES: &[&str] = &[
+	"direction", "box-sizing", "width", "height", "overflow-x", "overflow-y",
+	"border-top-width", "border-right-width", "border-bottom-width", "border-left-width", "border-style",
+	"padding-top", "padding-right", "padding-bottom", "padding-left",
+	"font-style", "font-variant", "font-weight", "font-stretch", "font-size", "font-size-adjust", "line-height", "font-family",
+	"text-align", "text-transform", "text-indent", "text-decoration", "letter-spacing", "word-spacing", "tab-size",
+];
+
+// backs `validity_signal`, so `set_custom_validity` can update it
+struct Validity(hobo::signal::Mutable<bool>);
+
+fn smooth_scroll_to(top: f64) -> web_sys::ScrollToOptions {
+	let options = web_sys::ScrollToOptions::new();
+	options.set_left(0.);
+	options.set_top(top);
+	options.set_behavior(if super::prefers_reduced_motion() { web_sys::ScrollBehavior::Instant } else { web_sys::ScrollBehavior::Smooth });
+	options
+}
+
+// px value of a `flip_if_offscreen` spacing, `em` is relative to `element`'s font size and `%` to `percent_base`
+fn resolve_spacing(unit: &css::Unit, element: &web_sys::Element, percent_base: f64) -> Option<f64> {
+	match unit {
+		css::Unit::Px(x) => Some(f64::from(x.into_inner())),
+		css::Unit::Em(x) => {
+			let font_size = window().get_computed_style(element).ok().flatten()
+				.and_then(|x| x.get_property_value("font-size").ok())
+				.and_then(|x| x.strip_suffix("px")?.parse::<f64>().ok())
+				.unwrap_or_else(super::units::root_font_size);
+			Some(f64::from(x.into_inner()) * font_size)
+		},
+		css::Unit::Calc(left, css::units::Operator::Plus, right) => Some(resolve_spacing(left, element, percent_base)? + resolve_spacing(right, element, percent_base)?),
+		css::Unit::Calc(left, css::units::Operator::Minus, right) => Some(resolve_spacing(left, element, percent_base)? - resolve_spacing(right, element, percent_base)?),
+		// not lengths, there's nothing sensible to flip by
+		css::Unit::Fr(_) | css::Unit::Duration(_) => None,
+		unit => Some(super::units::resolve_length_relative(unit, percent_base)),
+	}
+}
+
+pub(crate) fn form_control_validity(element: &web_sys::Element) -> bool {
+	if let Some(x) = element.dyn_ref::<web_sys::HtmlInputElement>() { x.check_validity() }
+	else if let Some(x) = element.dyn_ref::<web_sys::HtmlTextAreaElement>() { x.check_validity() }
+	else if let Some(x) = element.dyn_ref::<web_sys::HtmlSelectElement>() { x.check_validity() }
+	else { true }
+}
+
+pub(crate) fn form_control_validation_message(element: &web_sys::Element) -> String {
+	if let Some(x) = element.dyn_ref::<web_sys::HtmlInputElement>() { x.validation_message().unwrap_or_default() }
+	else if let Some(x) = element.dyn_ref::<web_sys::HtmlTextAreaElement>() { x.validation_message().unwrap_or_default() }
+	else if let Some(x) = element.dyn_ref::<web_sys::HtmlSelectElement>() { x.validation_message().unwrap_or_default() }
+	else { String::new() }
+}
+
+// `fooBar` -> `data-foo-bar`, as per the dataset name conversion rules
+fn dataset_attribute(key: &str) -> String {
+	let mut attribute = String::with_capacity(key.len() + 8);
+	attribute.push_str("data-");
+	for c in key.chars() {
+		if c.is_ascii_uppercase() {
+			attribute.push('-');
+			attribute.push(c.to_ascii_lowercase());
+		} else {
+			attribute.push(c);
+		}
+	}
+	attribute
+}
+
+thread_local! {
+	static SCROLL_POSITIONS: RefCell<std::collections::HashMap<String, (f64, f64)>> = RefCell::new(std::collections::HashMap::new());
+}
+
+/// A set of an element's edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Edges {
+	pub top: bool,
+	pub right: bool,
+	pub bottom: bool,
+	pub left: bool,
+}
+
+impl Edges {
+	pub const NONE: Self = Self { top: false, right: false, bottom: false, left: false };
+	pub const ALL: Self = Self { top: true, right: true, bottom: true, left: true };
+	pub const VERTICAL: Self = Self { top: true, right: false, bottom: true, left: false };
+	pub const HORIZONTAL: Self = Self { top: false, right: true, bottom: false, left: true };
+}
+
+const SCROLL_FADE_PX: f64 = 32.;
+
+// mask which fades out content at the edges where there's more to scroll to
+fn scroll_fade_properties(fades: Edges) -> Vec<css::Property> {
+	fn gradient(direction: &str, start: bool, end: bool) -> String {
+		let stop = |fade: bool| if fade { "transparent" } else { "black" };
+		format!("linear-gradient({direction}, {} 0, black {SCROLL_FADE_PX}px, black calc(100% - {SCROLL_FADE_PX}px), {} 100%)", stop(start), stop(end))
+	}
+
+	let mut gradients = Vec::new();
+	if fades.top || fades.bottom { gradients.push(gradient("to bottom", fades.top, fades.bottom)); }
+	if fades.left || fades.right { gradients.push(gradient("to right", fades.left, fades.right)); }
+	if gradients.is_empty() { return Vec::new(); }
+
+	let mask = gradients.join(", ");
+	let mut properties = vec![
+		css::Property::Raw(format!("mask-image:{mask};")),
+		css::Property::Raw(format!("-webkit-mask-image:{mask};")),
+	];
+	if gradients.len() > 1 {
+		properties.push(css::Property::Raw("mask-composite:intersect;".to_owned()));
+		properties.push(css::Property::Raw("-webkit-mask-composite:source-in;".to_owned()));
+	}
+	properties
+}
+
+// -1..=1 depending on how deep into the start or end zone `pos` is, 0 if it's outside of both
+fn edge_proximity(pos: f64, start: f64, end: f64, zone: f64) -> f64 {
+	if zone <= 0. || pos < start - zone || pos > end + zone { return 0.; }
+	if pos < start + zone { return -f64::min((start + zone - pos) / zone, 1.); }
+	if pos > end - zone { return f64::min((pos - (end - zone)) / zone, 1.); }
+	0.
+}
+
 pub trait AsElementExt: AsElement {
 	/// Adds an `data-name` attribute to the element with a value of T
 	#[must_use]
@@ -31,7 +345,7 @@ pub trait AsElementExt: AsElement {
 	/// See: `clicked()`.
 	#[must_use]
 	fn report_clicked(self) -> Self where Self: Sized + Copy + 'static {
-		self.report_clicked_on_window(window())
+		self.report_clicked_in(Context::global())
 	}
 
 	/// Adds the `Clicked` component to an element which allows you to tell whether it is currently being clicked on (mousedown active).
@@ -41,11 +355,21 @@ pub trait AsElementExt: AsElement {
 	/// See: `clicked()`.
 	#[must_use]
 	fn report_clicked_on_window(self, window: web_sys::Window) -> Self where Self: Sized + Copy + 'static {
+		self.report_clicked_in(Context::from_window(window))
+	}
+
+	/// Adds the `Clicked` component to an element which allows you to tell whether it is currently being clicked on (mousedown active).
+	///
+	/// Listens for the mouse being released on the `context`'s window.
+	///
+	/// See: `clicked()`.
+	#[must_use]
+	fn report_clicked_in(self, context: Context) -> Self where Self: Sized + Copy + 'static {
 		if self.try_get_cmp::<Clicked>().is_some() { return self; }
 
 		self.add_component(Clicked(false));
 		self.add_on_mouse_down(move |e| { e.prevent_default(); self.get_cmp_mut::<Clicked>().0 = true; });
-		self.add_bundle(window.on_mouse_up(move |_| self.get_cmp_mut::<Clicked>().0 = false));
+		self.add_bundle(context.window.on_mouse_up(move |_| self.get_cmp_mut::<Clicked>().0 = false));
 
 		self
 	}
@@ -74,6 +398,426 @@ pub trait AsElementExt: AsElement {
 	#[inline] fn bottom(&self) -> f64 { self.get_cmp::<web_sys::Element>().get_bounding_client_rect().bottom() }
 	#[inline] fn left(&self) -> f64 { self.get_cmp::<web_sys::Element>().get_bounding_client_rect().left() }
 
+	/// Converts viewport (client) coordinates into coordinates relative to the element's top left corner.
+	fn client_to_local(&self, client_x: f64, client_y: f64) -> (f64, f64) {
+		let rect = self.get_cmp::<web_sys::Element>().get_bounding_client_rect();
+		(client_x - rect.left(), client_y - rect.top())
+	}
+
+	/// Signal of the element's viewport-relative bounding rect, starting with the current one.
+	///
+	/// Updates whenever the rect could have changed - the element got resized, or anything got scrolled, or the window got resized.
+	/// Updates are coalesced to at most one per animation frame and only emitted if the rect actually changed.
+	/// All the observers and listeners are cleaned up when the element dies.
+	fn rect_signal(&self) -> impl hobo::signal::Signal<Item = web_sys::DomRect> + 'static { self.rect_signal_in(Context::global()) }
+
+	/// Same as `rect_signal`, but listening for scrolling and resizing of the `context`'s window.
+	fn rect_signal_in(&self, context: Context) -> impl hobo::signal::Signal<Item = web_sys::DomRect> + 'static {
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let rect = hobo::signal::Mutable::new(dom_element.get_bounding_client_rect());
+		let pending = Rc::new(Cell::new(false));
+
+		let update = Rc::new({
+			let dom_element = dom_element.clone();
+			let rect = rect.clone();
+			move || request_frame_coalesced(&pending, #[clown::clown] move || {
+				if element.is_dead() { return; }
+				let new_rect = honk!(dom_element).get_bounding_client_rect();
+				let rect = honk!(rect);
+				let old_rect = rect.lock_ref().clone();
+				let changed = new_rect.x() != old_rect.x() || new_rect.y() != old_rect.y() || new_rect.width() != old_rect.width() || new_rect.height() != old_rect.height();
+				if changed { rect.set(new_rect); }
+			})
+		});
+
+		element.add_bundle(observe_resize(&dom_element, #[clown::clown] move |_| honk!(update)()));
+		element.add_bundle(listen_capture(&context.window, "scroll", #[clown::clown] move |_: web_sys::Event| honk!(update)()));
+		element.add_bundle(context.window.on_resize(move |_| update()));
+
+		let signal = rect.signal_cloned();
+		element.add_bundle(rect);
+		signal
+	}
+
+	/// Signal of the pointer's position relative to the element's top left corner while it's over the element (or captured by it), `None` otherwise.
+	///
+	/// Moves are coalesced to at most one update per animation frame, e.g. for a hover-driven magnifier.
+	fn pointer_position_signal(&self) -> impl hobo::signal::Signal<Item = Option<(f64, f64)>> + 'static {
+		let element = self.as_element();
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let position = hobo::signal::Mutable::new(None);
+		let latest = Rc::new(Cell::new(None::<(f64, f64)>));
+		let pending = Rc::new(Cell::new(false));
+
+		element.add_bundle(listen(&target, "pointermove", #[clown::clown] move |e: web_sys::PointerEvent| {
+			let latest = &honk!(latest);
+			latest.set(Some((f64::from(e.client_x()), f64::from(e.client_y()))));
+			let latest = Rc::clone(latest);
+			let position = honk!(position).clone();
+			request_frame_coalesced(&honk!(pending), move || {
+				if element.is_dead() { return; }
+				position.set_neq(latest.get().map(|(x, y)| element.client_to_local(x, y)));
+			});
+		}));
+		element.add_bundle(listen(&target, "pointerleave", #[clown::clown] move |_: web_sys::Event| {
+			honk!(latest).set(None);
+			honk!(position).set_neq(None);
+		}));
+
+		let signal = position.signal();
+		element.add_bundle(position);
+		signal
+	}
+
+	/// Signal of the pointer's position relative to the element's top left corner on every `pointermove` over it, `None` on `pointerleave`.
+	///
+	/// Unlike `pointer_position_signal` this isn't coalesced per frame, e.g. for freehand drawing where every move matters.
+	fn pointer_local_signal(&self) -> impl hobo::signal::Signal<Item = Option<(f64, f64)>> + 'static {
+		let element = self.as_element();
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let position = hobo::signal::Mutable::new(None);
+
+		element.add_bundle(listen(&target, "pointermove", #[clown::clown] move |e: web_sys::PointerEvent| {
+			honk!(position).set_neq(Some(element.client_to_local(f64::from(e.client_x()), f64::from(e.client_y()))));
+		}));
+		element.add_bundle(listen(&target, "pointerleave", #[clown::clown] move |_: web_sys::Event| honk!(position).set_neq(None)));
+
+		let signal = position.signal();
+		element.add_bundle(position);
+		signal
+	}
+
+	/// Reads the given computed `properties` back into a `css::Style`, e.g. to persist a user-customized look and reapply it with `.class()`.
+	///
+	/// Values are parsed into typed properties for colors (`color`, `background-color`, `border-*-color`, `outline-color`, `text-decoration-color`, `fill`, `stroke`),
+	/// sizes and offsets (`width`/`height` and their `min-`/`max-`, `top`/`right`/`bottom`/`left`, `margin-*`, `padding-*`, `border-*-width`, `border-*-radius`,
+	/// `text-indent`, `outline-offset`, `stroke-width`), text (`font-size`, `font-weight`, `font-style`, `line-height`, `letter-spacing`, `word-spacing`,
+	/// `white-space`, `text-align`) and layout (`display`, `position`, `visibility`, `box-sizing`, `overflow-x`/`-y`, `float`, `clear`, `z-index`, `opacity`,
+	/// `pointer-events`, `object-fit`, `flex-direction`, `flex-wrap`).
+	/// Everything else falls back to `css::Property::Raw` - other properties and shorthands, and values hobo has no type for,
+	/// like `calc()`, colors that aren't serialized as `rgb()` or keywords such as `text-align: start`.
+	/// Properties that don't resolve to anything are skipped.
+	fn computed_css_style(&self, properties: &[&str]) -> css::Style {
+		let Ok(Some(computed)) = window().get_computed_style(&self.get_cmp::<web_sys::Element>()) else { return css::Style(Vec::new()); };
+		properties.iter()
+			.filter_map(|&name| {
+				let value = computed.get_property_value(name).ok()?;
+				if value.is_empty() { return None; }
+				Some(computed_style::parse_property(name, &value).unwrap_or_else(|| css::Property::Raw(format!("{name}:{value};"))))
+			})
+			.collect::<Vec<_>>()
+			.into()
+	}
+
+	/// Sets the given computed `properties` of `other` as inline styles on `self`, e.g. so a drag ghost looks exactly like the dragged element.
+	///
+	/// Inline styles take precedence over classes, properties that don't resolve to anything on `other` are left alone.
+	#[must_use]
+	fn copy_styles_from(self, other: &impl AsElement, properties: &[&str]) -> Self where Self: Sized {
+		let Ok(Some(computed)) = window().get_computed_style(&other.get_cmp::<web_sys::Element>()) else { return self; };
+		let Some(style) = self.get_cmp::<web_sys::Element>().dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style) else { return self; };
+		for &name in properties {
+			let Ok(value) = computed.get_property_value(name) else { continue; };
+			if !value.is_empty() { style.set_property(name, &value).ok(); }
+		}
+		self
+	}
+
+	/// Pixel coordinates of the caret in an `<input>` or `<textarea>`, relative to the element's top left corner and accounting for its scroll.
+	/// The y coordinate is the top of the caret's line.
+	///
+	/// Measured by laying out the text up to the caret in an invisible copy of the element that has the same text-affecting styles.
+	/// `None` for other elements or inputs that don't have a caret.
+	fn caret_coordinates(&self) -> Option<(f64, f64)> {
+		let element = self.get_cmp::<web_sys::Element>().clone();
+		let (value, caret, is_textarea) = if let Some(x) = element.dyn_ref::<web_sys::HtmlTextAreaElement>() {
+			(x.value(), x.selection_start().ok()??, true)
+		} else {
+			let x = element.dyn_ref::<web_sys::HtmlInputElement>()?;
+			(x.value(), x.selection_start().ok()??, false)
+		};
+		let computed = window().get_computed_style(&element).ok()??;
+		let document = super::document();
+		let body = document.body()?;
+
+		let mirror: web_sys::HtmlElement = document.create_element("div").ok()?.unchecked_into();
+		let style = mirror.style();
+		for property in CARET_MIRROR_PROPERTIES {
+			style.set_property(property, &computed.get_property_value(property).unwrap_or_default()).ok();
+		}
+		for (property, value) in [
+			("position", "absolute"),
+			("visibility", "hidden"),
+			("top", "0"),
+			("left", "-9999px"),
+			("white-space", if is_textarea { "pre-wrap" } else { "pre" }),
+			("overflow-wrap", if is_textarea { "break-word" } else { "normal" }),
+		] {
+			style.set_property(property, value).ok();
+		}
+
+		// selection offsets are in UTF-16 code units
+		let utf16 = value.encode_utf16().collect::<Vec<_>>();
+		let caret = (caret as usize).min(utf16.len());
+		mirror.set_text_content(Some(&String::from_utf16_lossy(&utf16[..caret])));
+		let marker: web_sys::HtmlElement = document.create_element("span").ok()?.unchecked_into();
+		// the rest of the text keeps wrapping the same, and an empty span wouldn't have a position
+		let rest = String::from_utf16_lossy(&utf16[caret..]);
+		marker.set_text_content(Some(if rest.is_empty() { "." } else { &rest }));
+		mirror.append_child(&marker).ok()?;
+		body.append_child(&mirror).ok()?;
+
+		let px = |property: &str| computed.get_property_value(property).ok().and_then(|x| x.trim_end_matches("px").parse::<f64>().ok()).unwrap_or_default();
+		let x = marker.offset_left() as f64 + px("border-left-width") - element.scroll_left() as f64;
+		let y = marker.offset_top() as f64 + px("border-top-width") - element.scroll_top() as f64;
+		mirror.remove();
+		Some((x, y))
+	}
+
+	/// Value of the `data-*` attribute for the dataset `key`, which is camelCase like with `HTMLElement.dataset` - `fooBar` reads `data-foo-bar`.
+	fn dataset_get(&self, key: &str) -> Option<String> {
+		self.get_cmp::<web_sys::Element>().get_attribute(&dataset_attribute(key))
+	}
+
+	/// Sets the `data-*` attribute for the camelCase dataset `key`, see `dataset_get`.
+	fn dataset_set(&self, key: &str, value: &str) {
+		self.set_attr(dataset_attribute(key), value);
+	}
+
+	/// Signal of the `data-*` attribute for the camelCase dataset `key` (see `dataset_get`), starting with the current value.
+	/// `None` while the attribute is missing.
+	fn dataset_signal(&self, key: &str) -> impl hobo::signal::Signal<Item = Option<String>> + 'static {
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let attribute = dataset_attribute(key);
+		let value = hobo::signal::Mutable::new(dom_element.get_attribute(&attribute));
+
+		let options = web_sys::MutationObserverInit::new();
+		options.set_attributes(true);
+		options.set_attribute_filter(&js_sys::Array::of1(&attribute.as_str().into()));
+		element.add_bundle(observe_mutations(&dom_element, &options, #[clown::clown] move |_| {
+			honk!(value).set_neq(honk!(dom_element).get_attribute(&attribute));
+		}));
+
+		let signal = value.signal_cloned();
+		element.add_bundle(value);
+		signal
+	}
+
+	/// Signal of the element's `textContent`, starting with the current one, e.g. to mirror a `contenteditable` or a third-party widget into app state.
+	fn text_content_signal(&self) -> impl hobo::signal::Signal<Item = String> + 'static {
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let text = hobo::signal::Mutable::new(dom_element.text_content().unwrap_or_default());
+
+		let options = web_sys::MutationObserverInit::new();
+		options.set_child_list(true);
+		options.set_subtree(true);
+		options.set_character_data(true);
+		element.add_bundle(observe_mutations(&dom_element, &options, #[clown::clown] move |_| {
+			honk!(text).set_neq(honk!(dom_element).text_content().unwrap_or_default());
+		}));
+
+		let signal = text.signal_cloned();
+		element.add_bundle(text);
+		signal
+	}
+
+	/// Signal of whether a form control (`<input>`, `<textarea>` or `<select>`) satisfies its constraints, starting with the current state.
+	///
+	/// Rechecked on `input`, `change` and `blur`, as well as whenever `set_custom_validity` is used.
+	/// Other elements are always valid.
+	fn validity_signal(&self) -> impl hobo::signal::Signal<Item = bool> + 'static where Self: Sized + Copy + 'static {
+		if let Some(validity) = self.try_get_cmp::<Validity>() { return validity.0.signal(); }
+
+		let element = self.as_element();
+		let validity = Validity(hobo::signal::Mutable::new(form_control_validity(&self.get_cmp::<web_sys::Element>())));
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		for name in ["input", "change", "blur"] {
+			element.add_bundle(listen(&target, name, move |_: web_sys::Event| {
+				let valid = form_control_validity(&element.get_cmp::<web_sys::Element>());
+				element.get_cmp::<Validity>().0.set_neq(valid);
+			}));
+		}
+		let signal = validity.0.signal();
+		element.add_component(validity);
+		signal
+	}
+
+	/// The browser's message describing why the form control is invalid, empty if it's valid or not a form control.
+	fn validation_message(&self) -> String { form_control_validation_message(&self.get_cmp::<web_sys::Element>()) }
+
+	/// Marks the form control as invalid with `message`, or valid again with an empty one - via the Constraint Validation API.
+	fn set_custom_validity(&self, message: &str) {
+		let element = self.get_cmp::<web_sys::Element>().clone();
+		if let Some(x) = element.dyn_ref::<web_sys::HtmlInputElement>() { x.set_custom_validity(message); }
+		else if let Some(x) = element.dyn_ref::<web_sys::HtmlTextAreaElement>() { x.set_custom_validity(message); }
+		else if let Some(x) = element.dyn_ref::<web_sys::HtmlSelectElement>() { x.set_custom_validity(message); }
+		if let Some(validity) = self.try_get_cmp::<Validity>() { validity.0.set_neq(form_control_validity(&element)); }
+	}
+
+	/// Focuses the first descendant that can be reached with Tab, returns whether there was one.
+	fn focus_first_focusable(&self) -> bool {
+		let Some(first) = tabbable_elements(&self.get_cmp::<web_sys::Element>()).into_iter().next() else { return false; };
+		first.focus().is_ok()
+	}
+
+	/// Signal of the element's `(width, height)`, starting with the current one.
+	///
+	/// Updates when a `ResizeObserver` reports a resize that changed either dimension by more than `SIZE_EPSILON_PX`,
+	/// so sub-pixel jitter doesn't cause re-renders.
+	fn size_signal(&self) -> impl hobo::signal::Signal<Item = (f64, f64)> + 'static {
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let rect = dom_element.get_bounding_client_rect();
+		let size = hobo::signal::Mutable::new((rect.width(), rect.height()));
+
+		element.add_bundle(observe_resize(&dom_element, #[clown::clown] move |_| {
+			let rect = honk!(dom_element).get_bounding_client_rect();
+			let size = &honk!(size);
+			let (width, height) = size.get();
+			if (rect.width() - width).abs() > SIZE_EPSILON_PX || (rect.height() - height).abs() > SIZE_EPSILON_PX {
+				size.set((rect.width(), rect.height()));
+			}
+		}));
+
+		let signal = size.signal();
+		element.add_bundle(size);
+		signal
+	}
+
+	/// Signal of how much of the element is visible in the viewport, from `0.` to `1.` - e.g. to fade or scale something in as it scrolls into view.
+	///
+	/// Starts at `0.` until the first intersection report, after that it updates in steps of `INTERSECTION_RATIO_STEP`.
+	fn intersection_ratio_signal(&self) -> impl hobo::signal::Signal<Item = f64> + 'static {
+		let ratio = hobo::signal::Mutable::new(0.);
+		let options = web_sys::IntersectionObserverInit::new();
+		let steps = (1. / INTERSECTION_RATIO_STEP).round() as u32;
+		let thresholds = (0..=steps).map(|i| JsValue::from_f64(f64::from(i) / f64::from(steps))).collect::<js_sys::Array>();
+		options.set_threshold_f64_sequence(&thresholds);
+
+		let observer = observe_intersection([&*self.get_cmp::<web_sys::Element>()], &options, #[clown::clown] move |entries| {
+			let Some(entry) = entries.last() else { return; };
+			honk!(ratio).set_neq(entry.intersection_ratio().clamp(0., 1.));
+		});
+
+		self.add_bundle(observer);
+		let signal = ratio.signal();
+		self.add_bundle(ratio);
+		signal
+	}
+
+	#[must_use]
+	fn on_relayout(self, f: impl FnMut() + 'static) -> Self where Self: Sized { self.add_on_relayout(f); self }
+
+	/// Calls `f` whenever the element's size or position changed, at most once per animation frame.
+	///
+	/// This is the general "my geometry changed, recompute" hook, see `rect_signal` for when exactly it's checked.
+	fn add_on_relayout(&self, mut f: impl FnMut() + 'static) {
+		let mut initial = true;
+		self.add_bundle(self.rect_signal().subscribe(move |_| if !std::mem::take(&mut initial) { f(); }));
+	}
+
+	#[must_use]
+	fn on_layout_change(self, f: impl FnMut(web_sys::DomRect) + 'static) -> Self where Self: Sized { self.add_on_layout_change(f); self }
+
+	/// Calls `f` with the element's bounding rect whenever its position or size changed, by reading the rect on every animation frame.
+	///
+	/// Unlike `add_on_relayout` this also catches the element getting moved by e.g. a sibling reflowing, which no observer or event reports.
+	/// The price is a `getBoundingClientRect` per frame - which forces a layout if anything is dirty - for as long as it runs,
+	/// so prefer `add_on_relayout` where that's enough. Polling stops when the element dies or the returned handle is cancelled.
+	fn add_on_layout_change(&self, mut f: impl FnMut(web_sys::DomRect) + 'static) -> AnimationHandle {
+		fn bounds(rect: &web_sys::DomRect) -> (f64, f64, f64, f64) { (rect.x(), rect.y(), rect.width(), rect.height()) }
+
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let mut last = bounds(&dom_element.get_bounding_client_rect());
+		super::animation(move |_| {
+			if element.is_dead() { return false; }
+			let rect = dom_element.get_bounding_client_rect();
+			if std::mem::replace(&mut last, bounds(&rect)) != last { f(rect); }
+			true
+		})
+	}
+
+	/// Signal of whether the element's content overflows its box in either direction, starting with the current state.
+	///
+	/// Recomputed (at most once per animation frame) when the element gets resized or its content changes.
+	fn overflowing_signal(&self) -> impl hobo::signal::Signal<Item = bool> + 'static {
+		fn is_overflowing(element: &web_sys::Element) -> bool {
+			element.scroll_width() > element.client_width() || element.scroll_height() > element.client_height()
+		}
+
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let overflowing = hobo::signal::Mutable::new(is_overflowing(&dom_element));
+		let pending = Rc::new(Cell::new(false));
+
+		let update = Rc::new({
+			let dom_element = dom_element.clone();
+			let overflowing = overflowing.clone();
+			move || request_frame_coalesced(&pending, #[clown::clown] move || {
+				if element.is_dead() { return; }
+				honk!(overflowing).set_neq(is_overflowing(&honk!(dom_element)));
+			})
+		});
+
+		let options = web_sys::MutationObserverInit::new();
+		options.set_child_list(true);
+		options.set_subtree(true);
+		options.set_character_data(true);
+		element.add_bundle(observe_mutations(&dom_element, &options, #[clown::clown] move |_| honk!(update)()));
+		element.add_bundle(observe_resize(&dom_element, move |_| update()));
+
+		let signal = overflowing.signal();
+		element.add_bundle(overflowing);
+		signal
+	}
+
+	/// Fades out a scroll container's content at the given `edges`, but only while there's more content to scroll to in that direction.
+	///
+	/// The fades are a `mask-image` on the element itself rather than overlay elements,
+	/// so they don't capture pointer events, don't scroll along with the content and work on top of any background.
+	/// Updates on scroll, when the element resizes and when its content changes.
+	#[must_use]
+	fn with_scroll_fades(self, edges: Edges) -> Self where Self: Sized + Copy + 'static {
+		struct ScrollFadesStyleTag;
+
+		let element = self.get_cmp::<web_sys::Element>().clone();
+		let fades = hobo::signal::Mutable::new(Edges::NONE);
+		let update = Rc::new(#[clown::clown] move || {
+			let element = &honk!(element);
+			let (scroll_top, scroll_left) = (f64::from(element.scroll_top()), f64::from(element.scroll_left()));
+			let max_top = f64::from(element.scroll_height() - element.client_height());
+			let max_left = f64::from(element.scroll_width() - element.client_width());
+			// scroll offsets can be fractional on hidpi screens
+			honk!(fades).set_neq(Edges {
+				top: edges.top && scroll_top > 0.5,
+				right: edges.right && scroll_left < max_left - 0.5,
+				bottom: edges.bottom && scroll_top < max_top - 0.5,
+				left: edges.left && scroll_left > 0.5,
+			});
+		});
+
+		// content growing or the container resizing moves the scroll extents even if it overflowed already, so these aren't `overflowing_signal`
+		let pending = Rc::new(Cell::new(false));
+		let schedule_update = Rc::new(#[clown::clown] move || {
+			let update = Rc::clone(&honk!(update));
+			request_frame_coalesced(&pending, move || if !self.is_dead() { update(); });
+		});
+		let options = web_sys::MutationObserverInit::new();
+		options.set_child_list(true);
+		options.set_subtree(true);
+		options.set_character_data(true);
+		self.add_bundle(observe_mutations(&element, &options, #[clown::clown] move |_| honk!(schedule_update)()));
+		self.add_bundle(observe_resize(&element, #[clown::clown] move |_| honk!(schedule_update)()));
+		schedule_update();
+
+		self.add_on_scroll(move |_| update());
+		self.class_typed_signal::<ScrollFadesStyleTag, _, _>(fades.signal().map(scroll_fade_properties))
+	}
+
 	/// Auto-flips an element if it would be off-screen, by mirroring the top/bottom/left/right positional properties appropriately.
 	///
 	/// This also counts as setting the prefered position for the element, so you do not need to add it in a class/style yourself.
@@ -85,66 +829,96 @@ pub trait AsElementExt: AsElement {
 	///
 	/// Note that it is not e.g. "100% + 8 px", but only the "margin".
 	///
-	/// Currently only px units are supported.
+	/// Non-px spacings are resolved to px at the time of the call - `rem` against the root font size, `em` against the element's own,
+	/// `%` against the parent's height (for `spacing_v`) or width (for `spacing_h`) and viewport units against the window.
+	/// An axis whose spacing isn't a length at all, like `fr`, only logs a warning and is left as is, the other one is still flipped.
+	///
+	/// For placement relative to an arbitrary reference element with alignment, shifting and arrows, see `position::compute_position`.
 	fn flip_if_offscreen(self, spacing_v: Option<css::Property>, spacing_h: Option<css::Property>) {
 		let parent = self.parent();
 		let self_height = self.height();
 		let self_width = self.width();
 		let window_height = window().inner_height().unwrap().as_f64().unwrap();
 		let window_width = window().inner_width().unwrap().as_f64().unwrap();
+		let element = self.get_cmp::<web_sys::Element>().clone();
 		let mut new_style = Vec::new();
 
 		if let Some(v) = spacing_v {
-			if let css::Property::Top(css::PositionOffset::Some(css::Unit::Px(f))) = v {
-				let vertical = f.into_inner() as f64;
-				let dimension = css::PositionOffset::Some(css::unit!(100% + vertical px));
-				let property = if parent.bottom() + vertical + self_height > window_height {
-					css::Property::Bottom(dimension)
+			if let css::Property::Top(css::PositionOffset::Some(unit)) = v {
+				if let Some(vertical) = resolve_spacing(&unit, &element, parent.height()) {
+					let dimension = css::PositionOffset::Some(css::unit!(100% + vertical px));
+					let property = if parent.bottom() + vertical + self_height > window_height {
+						css::Property::Bottom(dimension)
+					} else {
+						css::Property::Top(dimension)
+					};
+					new_style.push(property);
 				} else {
-					css::Property::Top(dimension)
-				};
-				new_style.push(property);
-			} else if let css::Property::Bottom(css::PositionOffset::Some(css::Unit::Px(f))) = v {
-				let vertical = f.into_inner() as f64;
-				let dimension = css::PositionOffset::Some(css::unit!(100% + vertical px));
-				let property = if parent.top() - vertical - self_height < 0. {
-					css::Property::Top(dimension)
+					log::warn!("Can't flip by {unit}, it isn't a length!");
+				}
+			} else if let css::Property::Bottom(css::PositionOffset::Some(unit)) = v {
+				if let Some(vertical) = resolve_spacing(&unit, &element, parent.height()) {
+					let dimension = css::PositionOffset::Some(css::unit!(100% + vertical px));
+					let property = if parent.top() - vertical - self_height < 0. {
+						css::Property::Top(dimension)
+					} else {
+						css::Property::Bottom(dimension)
+					};
+					new_style.push(property);
 				} else {
-					css::Property::Bottom(dimension)
-				};
-				new_style.push(property);
+					log::warn!("Can't flip by {unit}, it isn't a length!");
+				}
 			} else {
-				log::warn!("Flip on element with a non-pixel position! (or not top/bottom?)");
+				log::warn!("Flip on element with a non-length position! (or not top/bottom?)");
 			}
 		}
 
 		if let Some(h) = spacing_h {
-			if let css::Property::Left(css::PositionOffset::Some(css::Unit::Px(f))) = h {
-				let horizontal = f.into_inner() as f64;
-				let dimension = css::PositionOffset::Some(css::unit!(100% - horizontal px));
-				let property = if parent.right() + horizontal + self_width > window_width {
-					css::Property::Right(dimension)
+			if let css::Property::Left(css::PositionOffset::Some(unit)) = h {
+				if let Some(horizontal) = resolve_spacing(&unit, &element, parent.width()) {
+					let dimension = css::PositionOffset::Some(css::unit!(100% - horizontal px));
+					let property = if parent.right() + horizontal + self_width > window_width {
+						css::Property::Right(dimension)
+					} else {
+						css::Property::Left(dimension)
+					};
+					new_style.push(property);
 				} else {
-					css::Property::Left(dimension)
-				};
-				new_style.push(property);
-			} else if let css::Property::Right(css::PositionOffset::Some(css::Unit::Px(f))) = h {
-				let horizontal = f.into_inner() as f64;
-				let dimension = css::PositionOffset::Some(css::unit!(100% - horizontal px));
-				let property = if parent.left() - horizontal - self_width < 0. {
-					css::Property::Left(dimension)
+					log::warn!("Can't flip by {unit}, it isn't a length!");
+				}
+			} else if let css::Property::Right(css::PositionOffset::Some(unit)) = h {
+				if let Some(horizontal) = resolve_spacing(&unit, &element, parent.width()) {
+					let dimension = css::PositionOffset::Some(css::unit!(100% - horizontal px));
+					let property = if parent.left() - horizontal - self_width < 0. {
+						css::Property::Left(dimension)
+					} else {
+						css::Property::Right(dimension)
+					};
+					new_style.push(property);
 				} else {
-					css::Property::Right(dimension)
-				};
-				new_style.push(property);
+					log::warn!("Can't flip by {unit}, it isn't a length!");
+				}
 			} else {
-				log::warn!("Flip on element with a non-pixel position! (or not left/right?)");
+				log::warn!("Flip on element with a non-length position! (or not left/right?)");
 			}
 		}
 
 		self.set_style(new_style);
 	}
 
+	/// `flip_if_offscreen` that's redone whenever the window resizes, at most once per frame, for as long as the element lives.
+	///
+	/// Note that it's only the window - the parent moving around on its own, e.g. by scrolling, doesn't trigger it.
+	fn flip_if_offscreen_reactive(self, spacing_v: Option<css::Property>, spacing_h: Option<css::Property>) where Self: Sized + Copy + 'static {
+		self.flip_if_offscreen(spacing_v.clone(), spacing_h.clone());
+		let spacing = Rc::new((spacing_v, spacing_h));
+		let pending = Rc::new(Cell::new(false));
+		self.add_bundle(listen(&window(), "resize", move |_: web_sys::Event| {
+			let spacing = Rc::clone(&spacing);
+			request_frame_coalesced(&pending, move || if !self.is_dead() { self.flip_if_offscreen(spacing.0.clone(), spacing.1.clone()); });
+		}));
+	}
+
 	#[must_use]
 	fn hide_signal(self, signal: impl hobo::signal::Signal<Item=bool> + 'static) -> Self where Self: 'static {
 		struct HideSignalStyleTag;
@@ -157,19 +931,82 @@ pub trait AsElementExt: AsElement {
 		self.class_typed_signal::<HideSignalStyleTag, _, _>(signal.map(move |x| if x { css::properties![] } else { css::properties![css::display::none] }))
 	}
 
+	/// Binds a single length property to a signal, e.g. `width` to a progress value:
+	/// ```ignore
+	/// bar.length_signal(|x| css::Property::Width(css::Dimension::Some(x)), progress.signal(), LengthUnit::Percent)
+	/// ```
+	/// Non-finite values leave the property unset.
+	/// Every call uses the same typed class, so only the last `length_signal` on an element takes effect.
+	#[must_use]
+	fn length_signal(self, property: fn(css::Unit) -> css::Property, signal: impl hobo::signal::Signal<Item = f64> + 'static, unit: LengthUnit) -> Self where Self: 'static {
+		struct LengthSignalStyleTag;
+		self.class_typed_signal::<LengthSignalStyleTag, _, _>(signal.map(move |x| if x.is_finite() { vec![property(unit.unit(x))] } else { vec![] }))
+	}
+
+	/// Adds the element an async builder resolves with as a child, e.g. for a route whose component lives in a lazily loaded wasm chunk,
+	/// showing `fallback()` in its place until it's ready.
+	///
+	/// Each time `signal` changes, the fallback is shown again and the builder is called for the new value.
+	/// A previous build that's still pending gets dropped, so a route that was navigated away from before it loaded never shows up.
+	#[must_use]
+	fn lazy_child_signal<K, Fut, E, F>(
+		self,
+		signal: impl hobo::signal::Signal<Item = K> + 'static,
+		fallback: impl Fn() -> F + 'static,
+		mut build: impl FnMut(K) -> Fut + 'static,
+	) -> Self where
+		Self: Sized + 'static,
+		Fut: std::future::Future<Output = E> + 'static,
+		E: AsElement,
+		F: AsElement,
+	{
+		self.child_signal(signal
+			.map(move |x| hobo::signal::from_future(build(x)))
+			.flatten()
+			.map(move |x| x.map_or_else(|| fallback().as_element(), |x| x.as_element()))
+		)
+	}
+
+	#[must_use]
+	fn on_double_click(self, f: impl FnMut(web_sys::MouseEvent) + 'static) -> Self where Self: Sized { self.add_on_double_click(f); self }
+
+	/// Calls `f` on `dblclick` of the element itself with the raw event, e.g. to read modifier keys or coordinates.
+	///
+	/// Coexists with `report_clicked` and `on_slide`, which go by `mousedown`/`mouseup` instead.
+	fn add_on_double_click(&self, f: impl FnMut(web_sys::MouseEvent) + 'static) {
+		self.add_bundle(listen(&self.get_cmp::<web_sys::EventTarget>(), "dblclick", f));
+	}
+
 	#[must_use]
 	fn on_slide(self, f: impl FnMut(f64) + 'static) -> Self where Self: Sized + Copy + 'static { self.add_on_slide(f); self }
 
 	/// Provides a closure which triggers on mouse move, only while the element is clicked.
 	/// It captures a normalized `f64` which indicates where the mouse currently is on the element (left-right).
 	fn add_on_slide(self, mut f: impl FnMut(f64) + 'static) where Self: Sized + Copy + 'static {
+		self.add_on_slide_2d(move |(x, _)| f(x));
+	}
+
+	#[must_use]
+	fn on_slide_vertical(self, f: impl FnMut(f64) + 'static) -> Self where Self: Sized + Copy + 'static { self.add_on_slide_vertical(f); self }
+
+	/// Same as `add_on_slide`, but top-bottom, e.g. for a volume column.
+	fn add_on_slide_vertical(self, mut f: impl FnMut(f64) + 'static) where Self: Sized + Copy + 'static {
+		self.add_on_slide_2d(move |(_, y)| f(y));
+	}
+
+	#[must_use]
+	fn on_slide_2d(self, f: impl FnMut((f64, f64)) + 'static) -> Self where Self: Sized + Copy + 'static { self.add_on_slide_2d(f); self }
+
+	/// Same as `add_on_slide`, but along both axes for 2D pads - the closure gets `(left-right, top-bottom)`, both normalized.
+	fn add_on_slide_2d(self, mut f: impl FnMut((f64, f64)) + 'static) where Self: Sized + Copy + 'static {
 		self
 			.report_clicked()
 			.add_bundle(window().on_mouse_move(move |mouse_event: web_sys::MouseEvent| {
 				if !self.clicked() { return; }
-				let mouse_x = mouse_event.client_x() as f64;
-				let position = f64::clamp((mouse_x - self.left()) / self.width(), 0.0, 1.0);
-				f(position);
+				let (mouse_x, mouse_y) = (mouse_event.client_x() as f64, mouse_event.client_y() as f64);
+				let x = f64::clamp((mouse_x - self.left()) / self.width(), 0.0, 1.0);
+				let y = f64::clamp((mouse_y - self.top()) / self.height(), 0.0, 1.0);
+				f((x, y));
 			}));
 	}
 
@@ -178,6 +1015,65 @@ pub trait AsElementExt: AsElement {
 		self.on_slide(move |e| f(&self, e))
 	}
 
+	#[must_use]
+	fn on_zoom(self, f: impl FnMut(ZoomInfo) + 'static) -> Self where Self: Sized + Copy + 'static { self.add_on_zoom(f); self }
+
+	/// Provides a closure which triggers on ctrl+wheel (which is also what trackpad pinching produces) and on two-finger touch pinching.
+	/// Both are normalized into a `ZoomInfo` with the scale factor relative to the previous step and the focal point in element-local coordinates.
+	/// Browser zooming is prevented for these gestures over the element, applying, accumulating and clamping the scale is up to the caller.
+	fn add_on_zoom(self, f: impl FnMut(ZoomInfo) + 'static) where Self: Sized + Copy + 'static {
+		let f = Rc::new(RefCell::new(f));
+		let pinch_distance = Rc::new(Cell::new(None::<f64>));
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+
+		self.add_bundle(listen(&target, "wheel", #[clown::clown] move |e: web_sys::WheelEvent| {
+			if !e.ctrl_key() { return; }
+			e.prevent_default();
+			let delta = e.delta_y() * match e.delta_mode() {
+				web_sys::WheelEvent::DOM_DELTA_LINE => 16.,
+				web_sys::WheelEvent::DOM_DELTA_PAGE => self.height(),
+				_ => 1.,
+			};
+			let (focal_x, focal_y) = self.client_to_local(e.client_x() as f64, e.client_y() as f64);
+			(*honk!(f)).borrow_mut()(ZoomInfo { scale: f64::exp(-delta * WHEEL_ZOOM_SPEED), focal_x, focal_y });
+		}));
+		self.add_bundle(listen(&target, "touchstart", #[clown::clown] move |e: web_sys::TouchEvent| {
+			honk!(pinch_distance).set(touch_pair(&e).map(|(_, distance)| distance));
+		}));
+		self.add_bundle(listen(&target, "touchmove", #[clown::clown] move |e: web_sys::TouchEvent| {
+			let Some(((client_x, client_y), distance)) = touch_pair(&e) else { return; };
+			e.prevent_default();
+			let pinch_distance = &honk!(pinch_distance);
+			let previous = pinch_distance.replace(Some(distance));
+			let Some(previous) = previous.filter(|&x| x > 0.) else { return; };
+			let (focal_x, focal_y) = self.client_to_local(client_x, client_y);
+			(*honk!(f)).borrow_mut()(ZoomInfo { scale: distance / previous, focal_x, focal_y });
+		}));
+		self.add_bundle(listen(&target, "touchend", move |e: web_sys::TouchEvent| {
+			pinch_distance.set(touch_pair(&e).map(|(_, distance)| distance));
+		}));
+	}
+
+	#[must_use]
+	fn on_disconnect(self, f: impl FnMut() + 'static) -> Self where Self: Sized { self.add_on_disconnect(f); self }
+
+	/// Calls `f` whenever the element gets detached from the document, whether by itself or along with an ancestor,
+	/// e.g. to pause a video whose element is cached for re-attaching later.
+	///
+	/// Unlike dropping bundles, this doesn't need the entity to die. Gets called again if the element is reattached and detached again.
+	fn add_on_disconnect(&self, mut f: impl FnMut() + 'static) {
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let Some(root) = super::document().document_element() else { return; };
+		let mut connected = dom_element.is_connected();
+		let options = web_sys::MutationObserverInit::new();
+		options.set_child_list(true);
+		options.set_subtree(true);
+		self.add_bundle(observe_mutations(&root, &options, move |_| {
+			let was_connected = std::mem::replace(&mut connected, dom_element.is_connected());
+			if was_connected && !connected { f(); }
+		}));
+	}
+
 	#[must_use]
 	fn on_next_flow(self, f: impl FnOnce() + 'static) -> Self where Self: Sized + Copy + 'static {
 		self.set_on_next_flow(f); self
@@ -207,25 +1103,869 @@ pub trait AsElementExt: AsElement {
 	///
 	/// Creates a new observer with the passed in parameters,
 	/// saves the closure and the observer as a component,
-	/// and then immediately calls observe on the element,
-	fn set_on_intersection(self, f: impl FnMut(Vec<web_sys::IntersectionObserverEntry>) + 'static) {
+	/// and then immediately calls observe on the element.
+	/// The returned handle can stop and resume observing, e.g. for one-shot lazy loading.
+	fn set_on_intersection(self, f: impl FnMut(Vec<web_sys::IntersectionObserverEntry>) + 'static) -> ObserverHandle {
 		let closure = closure_mut(f);
 
 		let observer = web_sys::IntersectionObserver::new_with_options(closure.as_ref().unchecked_ref(), &web_sys::IntersectionObserverInit::new()).unwrap();
 		observer.observe(&self.get_cmp::<web_sys::Element>());
 
 		self.add_component(closure);
-		self.add_component(observer);
+		self.add_component(observer.clone());
+		ObserverHandle::intersection(self.as_element(), observer)
+	}
+
+	#[must_use]
+	fn on_content_resize(self, f: impl FnMut(Vec<web_sys::ResizeObserverEntry>) + 'static) -> Self where Self: Sized {
+		self.set_on_content_resize(f);
+		self
+	}
+
+	/// Boilerplate for using the [ResizeObserver API](https://developer.mozilla.org/en-US/docs/Web/API/Resize_Observer_API),
+	/// the counterpart of `set_on_intersection`.
+	///
+	/// Calls `f` whenever the element's content box changes size, starting with its initial size right after observing.
+	/// The observer along with the closure is kept on the element and disconnected when it dies, see `size_signal` for a signal on top of the same observer.
+	/// The returned handle can stop and resume observing earlier.
+	fn set_on_content_resize(&self, f: impl FnMut(Vec<web_sys::ResizeObserverEntry>) + 'static) -> ObserverHandle {
+		let guard = observe_resize(&self.get_cmp::<web_sys::Element>(), f);
+		let handle = ObserverHandle::resize(self.as_element(), guard.observer().clone());
+		self.add_bundle(guard);
+		handle
+	}
+
+	#[must_use]
+	fn autoscroll_on_drag_edge(self, config: EdgeScrollConfig) -> Self where Self: Sized + Copy + 'static { self.add_autoscroll_on_drag_edge(config); self }
+
+	/// Scrolls the element while something is being dragged close to its edges,
+	/// faster the closer the pointer is to the edge.
+	///
+	/// Both native drag and drop (`dragover`) and mouse dragging (mouse moving with the primary button held) count as dragging.
+	/// Scrolling is driven by `animation` and stops as soon as the drag ends or the pointer leaves the zone.
+	fn add_autoscroll_on_drag_edge(self, config: EdgeScrollConfig) where Self: Sized + Copy + 'static {
+		let element = self.as_element();
+		let pointer = Rc::new(Cell::new(None::<(f64, f64)>));
+		let running = Rc::new(Cell::new(false));
+
+		let on_move = Rc::new(#[clown::clown] move |e: web_sys::MouseEvent| {
+			let pointer = &honk!(pointer);
+			pointer.set(Some((e.client_x() as f64, e.client_y() as f64)));
+			let running = &honk!(running);
+			if running.replace(true) { return; }
+
+			let pointer = Rc::clone(pointer);
+			let running = Rc::clone(running);
+			super::animation(move |delta_t| {
+				let velocity = if element.is_dead() { None } else { pointer.get().map(|(x, y)| {
+					let rect = element.get_cmp::<web_sys::Element>().get_bounding_client_rect();
+					let inside_h = x >= rect.left() && x <= rect.right();
+					let inside_v = y >= rect.top() && y <= rect.bottom();
+					(
+						if inside_v { edge_proximity(x, rect.left(), rect.right(), config.zone_px) } else { 0. },
+						if inside_h { edge_proximity(y, rect.top(), rect.bottom(), config.zone_px) } else { 0. },
+					)
+				}) };
+				let Some((h, v)) = velocity.filter(|&(h, v)| h != 0. || v != 0.) else { running.set(false); return false; };
+				let distance = config.speed * delta_t / 1000.;
+				element.get_cmp::<web_sys::Element>().scroll_by_with_x_and_y(h * distance, v * distance);
+				true
+			});
+		});
+
+		let window = window();
+		self.add_bundle(listen(&window, "dragover", #[clown::clown] move |e: web_sys::MouseEvent| honk!(on_move)(e)));
+		self.add_bundle(window.on_mouse_move(move |e| if e.buttons() & 1 != 0 { on_move(e) }));
+		for event in ["dragend", "drop", "mouseup"] {
+			self.add_bundle(listen(&window, event, #[clown::clown] move |_: web_sys::Event| honk!(pointer).set(None)));
+		}
+	}
+
+	/// Makes the element a source for native (HTML5) drag and drop, which also works across windows.
+	///
+	/// `data` is called on each `dragstart` and returns the mime type and the payload to put into the `DataTransfer`.
+	#[must_use]
+	#[expect(clippy::wrong_self_convention)]
+	fn as_drag_source(self, data: impl Fn() -> (String, String) + 'static) -> Self where Self: Sized {
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		self.add_bundle(listen(&target, "dragstart", move |e: web_sys::DragEvent| {
+			let Some(data_transfer) = e.data_transfer() else { return; };
+			let (mime, payload) = data();
+			if let Err(e) = data_transfer.set_data(&mime, &payload) { log::warn!("failed to set drag data: {e:?}"); }
+		}));
+		self.attr(web_str::draggable(), "true")
+	}
+
+	/// Lets the file made by `data` be dragged out of the page, e.g. onto the desktop, via the `DownloadURL` drag data (Chromium-only).
+	///
+	/// `filename` and `data` are called on each `dragstart`, the data is served from an object URL that gets revoked a minute later.
+	#[must_use]
+	fn draggable_file(self, filename: impl Fn() -> String + 'static, mime: &str, data: impl Fn() -> Vec<u8> + 'static) -> Self where Self: Sized {
+		const REVOKE_AFTER: std::time::Duration = std::time::Duration::from_secs(60);
+
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let mime = mime.to_owned();
+		self.add_bundle(listen(&target, "dragstart", move |e: web_sys::DragEvent| {
+			let Some(data_transfer) = e.data_transfer() else { return; };
+			let bytes = js_sys::Uint8Array::from(data().as_slice());
+			let options = web_sys::BlobPropertyBag::new();
+			options.set_type(&mime);
+			let url = match web_sys::Blob::new_with_u8_array_sequence_and_options(&js_sys::Array::of1(&bytes), &options).and_then(|x| web_sys::Url::create_object_url_with_blob(&x)) {
+				Ok(x) => x,
+				Err(e) => { log::warn!("failed to create drag file: {e:?}"); return; },
+			};
+			if let Err(e) = data_transfer.set_data("DownloadURL", &format!("{mime}:{}:{url}", filename())) { log::warn!("failed to set drag data: {e:?}"); }
+			wasm_bindgen_futures::spawn_local(async move {
+				async_timer::new_timer(REVOKE_AFTER).await;
+				web_sys::Url::revoke_object_url(&url).ok();
+			});
+		}));
+		self.attr(web_str::draggable(), "true")
+	}
+
+	/// Calls `on_dismiss` whenever a popover-like element should close - a pointer pressed outside of it, Escape pressed anywhere,
+	/// or focus moving from within it to something outside.
+	///
+	/// Outside presses are detected on `pointerdown` so they also work for presses that start a drag or a scroll.
+	/// Focus leaving the document (e.g. switching tabs) or going nowhere doesn't count - clicking somewhere unfocusable outside is reported as `OutsideClick` instead.
+	/// Nothing is closed or removed, that's up to `on_dismiss`.
+	#[must_use]
+	fn dismissable(self, on_dismiss: impl FnMut(DismissReason) + 'static) -> Self where Self: Sized {
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let document = super::document();
+		let on_dismiss = Rc::new(RefCell::new(on_dismiss));
+		let is_outside = Rc::new(move |target: Option<web_sys::EventTarget>| {
+			target.and_then(|x| x.dyn_into::<web_sys::Node>().ok()).is_some_and(|x| !dom_element.contains(Some(&x)))
+		});
+
+		self.add_bundle(listen_capture(&document, "pointerdown", #[clown::clown] move |e: web_sys::PointerEvent| {
+			if honk!(is_outside)(e.target()) { (honk!(on_dismiss).borrow_mut())(DismissReason::OutsideClick); }
+		}));
+		self.add_bundle(listen(&document, "keydown", #[clown::clown] move |e: web_sys::KeyboardEvent| {
+			if e.key() == "Escape" { (honk!(on_dismiss).borrow_mut())(DismissReason::Escape); }
+		}));
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		self.bundle(listen(&target, "focusout", move |e: web_sys::FocusEvent| {
+			let related = e.related_target();
+			if related.is_some() && is_outside(related) { (on_dismiss.borrow_mut())(DismissReason::FocusLost); }
+		}))
+	}
+
+	/// Only lets a native drag of the element (see `as_drag_source`/`draggable_file`) start when it's grabbed by its `selector`-matching part,
+	/// e.g. the grip of a card that has buttons or inputs inside.
+	///
+	/// `dragstart` is always targeted at the draggable element itself, so the check is `closest(selector)` on the preceding `pointerdown`'s target.
+	/// Drags of draggable descendants such as links and images aren't affected.
+	#[must_use]
+	fn drag_handle(self, selector: &str) -> Self where Self: Sized {
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let grabbed = Rc::new(Cell::new(false));
+		let selector = selector.to_owned();
+
+		// capturing so descendants stopping propagation can't leave a stale value behind
+		self.add_bundle(listen_capture(&target, "pointerdown", #[clown::clown] move |e: web_sys::PointerEvent| {
+			let handle = e.target()
+				.and_then(|x| x.dyn_into::<web_sys::Element>().ok())
+				.and_then(|x| x.closest(&selector).ok().flatten());
+			let dom_element = &honk!(dom_element);
+			honk!(grabbed).set(handle.is_some_and(|x| dom_element.contains(Some(&x))));
+		}));
+		self.bundle(listen(&target, "dragstart", move |e: web_sys::DragEvent| {
+			let is_self = e.target().and_then(|x| x.dyn_into::<web_sys::Element>().ok()).is_some_and(|x| x == dom_element);
+			if is_self && !grabbed.get() { e.prevent_default(); }
+		}))
+	}
+
+	/// Makes the element accept native (HTML5) drops of data with the `accept` mime type, the payload is passed to `on_drop`.
+	///
+	/// Drags carrying other types of data are not allowed to be dropped.
+	#[must_use]
+	#[expect(clippy::wrong_self_convention)]
+	fn as_drop_target(self, accept: &str, mut on_drop: impl FnMut(String) + 'static) -> Self where Self: Sized {
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let accepts = { let accept = accept.to_owned(); move |e: &web_sys::DragEvent| {
+			e.data_transfer().is_some_and(|x| x.types().includes(&accept.as_str().into(), 0))
+		} };
+
+		self.add_bundle(listen(&target, "dragover", #[clown::clown] move |e: web_sys::DragEvent| if honk!(accepts)(&e) { e.prevent_default(); }));
+		self.add_bundle(listen(&target, "drop", { let accept = accept.to_owned(); move |e: web_sys::DragEvent| {
+			if !accepts(&e) { return; }
+			e.prevent_default();
+			let Some(data_transfer) = e.data_transfer() else { return; };
+			match data_transfer.get_data(&accept) {
+				Ok(payload) => on_drop(payload),
+				Err(e) => log::warn!("failed to get drop data: {e:?}"),
+			}
+		} }));
+		self
+	}
+
+	/// Scroll-spy for e.g. a docs sidebar - a signal of which of `sections` is currently active, so that the matching nav link can be highlighted.
+	///
+	/// The active section is the topmost one intersecting the viewport, if none are intersecting the previously active one stays active.
+	/// Starts with `None` until the first intersection report.
+	/// The observer lives as long as `self` (typically the nav element).
+	fn scroll_spy<E: AsElement>(&self, sections: impl IntoIterator<Item = E>) -> impl hobo::signal::Signal<Item = Option<usize>> + 'static {
+		let sections = sections.into_iter().map(|x| x.get_cmp::<web_sys::Element>().clone()).collect::<Vec<_>>();
+		let active = hobo::signal::Mutable::new(None);
+		let mut intersecting = vec![false; sections.len()];
+
+		let observer = observe_intersection(&sections, &web_sys::IntersectionObserverInit::new(), #[clown::clown] move |entries| {
+			let sections = &honk!(sections);
+			for entry in entries {
+				let target = entry.target();
+				let Some(i) = sections.iter().position(|x| *x == target) else { continue; };
+				intersecting[i] = entry.is_intersecting();
+			}
+
+			let topmost = sections.iter().enumerate()
+				.filter(|&(i, _)| intersecting[i])
+				.map(|(i, x)| (i, x.get_bounding_client_rect().top()))
+				.min_by(|a, b| a.1.total_cmp(&b.1))
+				.map(|(i, _)| i);
+			if let Some(i) = topmost { honk!(active).set_neq(Some(i)); }
+		});
+
+		self.add_bundle(observer);
+		let signal = active.signal();
+		self.add_bundle(active);
+		signal
+	}
+
+	/// Renders the element into a PNG blob - serializes it into an SVG `<foreignObject>` with all computed styles inlined,
+	/// draws that onto a canvas and exports it.
+	///
+	/// Caveats:
+	/// * web fonts are not embedded, text is rendered with whatever the SVG image context has access to (usually fallback fonts)
+	/// * cross-origin images taint the canvas, in which case the export fails and this resolves with `None`
+	/// * external stylesheets, pseudo-elements and the contents of `<canvas>`/`<video>`/`<iframe>` descendants are not captured
+	fn element_to_png(&self) -> impl std::future::Future<Output = Option<web_sys::Blob>> + 'static {
+		to_png::element_to_png(self.get_cmp::<web_sys::Element>().clone())
+	}
+
+	/// Smoothly moves the element towards the pointer every frame, e.g. for a custom cursor follower.
+	///
+	/// `ease` is the fraction of the remaining distance covered per frame (at 60 fps, adjusted for the actual frame rate),
+	/// so `1.` sticks to the pointer and values closer to `0.` lag behind more.
+	///
+	/// Only the `transform` is touched, so the element should be e.g. `position: fixed` at the top left with `pointer-events: none`.
+	/// Stops when the element dies.
+	#[must_use]
+	fn follow_pointer(self, ease: f64) -> Self where Self: Sized + Copy + 'static {
+		let element = self.as_element();
+		let target = Rc::new(Cell::new(None::<(f64, f64)>));
+		self.add_bundle(listen(&window(), "pointermove", #[clown::clown] move |e: web_sys::MouseEvent| {
+			honk!(target).set(Some((e.client_x() as f64, e.client_y() as f64)));
+		}));
+
+		let ease = ease.clamp(0., 1.);
+		let mut position = None::<(f64, f64)>;
+		super::animation(move |delta_t| {
+			if element.is_dead() { return false; }
+			let Some((target_x, target_y)) = target.get() else { return true; };
+			let (x, y) = position.get_or_insert((target_x, target_y));
+			let t = 1. - (1. - ease).powf(delta_t / (1000. / 60.));
+			*x += (target_x - *x) * t;
+			*y += (target_y - *y) * t;
+			element.get_cmp::<web_sys::HtmlElement>().style().set_property("transform", &format!("translate({x}px, {y}px)")).ok();
+			true
+		});
+
+		self
+	}
+
+	/// Calls `f` whenever `combo` is pressed anywhere in the document for as long as the element lives, see `shortcuts::on_key_combo`.
+	#[must_use]
+	fn on_key_combo(self, combo: KeyCombo, f: impl FnMut(web_sys::KeyboardEvent) + 'static) -> Self where Self: Sized {
+		self.bundle(super::shortcuts::on_key_combo(combo, f))
+	}
+
+	/// Same as `on_key_combo`, but also lists the shortcut with `description` under `group` in `shortcuts::overlay` while the element lives.
+	#[must_use]
+	fn on_key_combo_described(self, combo: KeyCombo, group: &str, description: &str, f: impl FnMut(web_sys::KeyboardEvent) + 'static) -> Self where Self: Sized {
+		self
+			.bundle(super::shortcuts::register(Shortcut::new(combo.clone(), group, description)))
+			.on_key_combo(combo, f)
+	}
+
+	/// Calls `f` on the element's own `animationstart`/`animationiteration`/`animationend`, e.g. to run logic once a pure-CSS animation finishes.
+	///
+	/// With `name`, only the CSS animation with that `animation-name` counts. Events bubbling up from descendants' animations are ignored.
+	#[must_use]
+	fn on_animation_event(self, name: Option<&str>, kind: AnimEventKind, mut f: impl FnMut() + 'static) -> Self where Self: Sized {
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let name = name.map(str::to_owned);
+		self.bundle(listen(&target, kind.event_name(), #[clown::clown] move |e: web_sys::AnimationEvent| {
+			if e.target().as_ref() != Some(&honk!(target)) { return; }
+			if name.as_ref().is_some_and(|name| *name != e.animation_name()) { return; }
+			f();
+		}))
+	}
+
+	/// Infinite scroll - calls `f` when the element is scrolled to within `threshold_px` of its bottom, e.g. to load the next page of results.
+	///
+	/// After firing it stays quiet until the content grows (the next page got added) or the user scrolls back out of the threshold,
+	/// so momentum scrolling doesn't fire it over and over. When added content still doesn't fill the element past the threshold it fires again right away.
+	#[must_use]
+	fn on_scroll_end(self, threshold_px: f64, f: impl FnMut() + 'static) -> Self where Self: Sized + 'static {
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		// the `scroll_height` at the time of the last firing
+		let fired_at = Rc::new(Cell::new(None::<i32>));
+		let f = Rc::new(RefCell::new(f));
+		let check = Rc::new({
+			let dom_element = dom_element.clone();
+			move || {
+				let remaining = f64::from(dom_element.scroll_height() - dom_element.scroll_top() - dom_element.client_height());
+				if remaining > threshold_px { fired_at.set(None); return; }
+				if fired_at.get() == Some(dom_element.scroll_height()) { return; }
+				fired_at.set(Some(dom_element.scroll_height()));
+				(f.borrow_mut())();
+			}
+		});
+
+		let pending = Rc::new(Cell::new(false));
+		let options = web_sys::MutationObserverInit::new();
+		options.set_child_list(true);
+		options.set_subtree(true);
+		self.add_bundle(observe_mutations(&dom_element, &options, #[clown::clown] move |_| {
+			let check = Rc::clone(&honk!(check));
+			request_frame_coalesced(&honk!(pending), move || if !element.is_dead() { check(); });
+		}));
+		self.bundle(listen(&dom_element, "scroll", move |_: web_sys::Event| check()))
+	}
+
+	/// Makes the element as tall as the visible viewport, without the jumps `100vh` causes on mobile when the URL bar collapses.
+	///
+	/// Uses `100dvh` where it's supported. Elsewhere, `--vh` on `<html>` is kept at 1% of `window.innerHeight`
+	/// as long as the element lives, and the height is `calc(var(--vh) * 100)`.
+	#[must_use]
+	fn fill_viewport_height(self) -> Self where Self: Sized {
+		struct FillViewportHeightTag;
+		if web_sys::css::supports_with_value("height", "100dvh").unwrap_or(false) {
+			return self.class_typed::<FillViewportHeightTag>(vec![css::Property::Raw("height:100dvh;".to_owned())]);
+		}
+
+		let update_vh = || {
+			let window = window();
+			let Some(height) = window.inner_height().ok().and_then(|x| x.as_f64()) else { return; };
+			let Some(root) = window.document().and_then(|x| x.document_element()).and_then(|x| x.dyn_into::<web_sys::HtmlElement>().ok()) else { return; };
+			root.style().set_property("--vh", &format!("{}px", height / 100.)).ok();
+		};
+		update_vh();
+		self
+			.class_typed::<FillViewportHeightTag>(vec![css::Property::Raw("height:calc(var(--vh, 1vh) * 100);".to_owned())])
+			.bundle(listen(&window(), "resize", move |_: web_sys::Event| update_vh()))
+	}
+
+	/// Hold-to-repeat, e.g. for +/- steppers.
+	///
+	/// Calls `f` right away when the element is pressed, again after `initial_delay_ms`, and then every `repeat_ms` until
+	/// the pointer is released, cancelled or leaves the element. A `repeat_ms` of 0 repeats every millisecond.
+	#[must_use]
+	fn on_press_repeat(self, initial_delay_ms: u32, repeat_ms: u32, f: impl FnMut() + 'static) -> Self where Self: Sized + Copy + 'static {
+		let f = Rc::new(RefCell::new(f));
+		let repeating = Rc::new(RefCell::new(None::<hobo::signals_ext::Subscription>));
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+
+		self.add_bundle(listen(&target, "pointerdown", #[clown::clown] move |e: web_sys::MouseEvent| {
+			if e.button() != 0 { return; }
+			let f = &honk!(f);
+			(**f).borrow_mut()();
+
+			let f = Rc::clone(f);
+			let (handle, fut) = hobo::futures_signals::cancelable_future(async move {
+				async_timer::new_timer(std::time::Duration::from_millis(initial_delay_ms.into())).await;
+				(*f).borrow_mut()();
+				// a zero-length interval would spin instead of yielding
+				let mut interval = async_timer::interval(std::time::Duration::from_millis(repeat_ms.max(1).into()));
+				loop {
+					interval.wait().await;
+					(*f).borrow_mut()();
+				}
+			}, Default::default);
+			wasm_bindgen_futures::spawn_local(fut);
+			*honk!(repeating).borrow_mut() = Some(handle);
+		}));
+
+		let stop = Rc::new(move || { repeating.borrow_mut().take(); });
+		self.add_bundle(listen(&target, "pointerleave", #[clown::clown] move |_: web_sys::Event| honk!(stop)()));
+		for event in ["pointerup", "pointercancel"] {
+			self.add_bundle(listen(&window(), event, #[clown::clown] move |_: web_sys::Event| honk!(stop)()));
+		}
+		self
+	}
+
+	/// Two-step confirmation for destructive buttons - the first click swaps the label to `confirm_label`
+	/// and only a second click within `CONFIRM_ACTION_TIMEOUT_MS` runs `f`.
+	///
+	/// The label reverts when time runs out or the button loses focus. Meant for buttons labelled with just text,
+	/// since the label is put back as text. While waiting for the second click the button has a `data-confirming` attribute for styling.
+	#[must_use]
+	fn confirm_action(self, confirm_label: &str, mut f: impl FnMut() + 'static) -> Self where Self: Sized + Copy + 'static {
+		let confirm_label = confirm_label.to_owned();
+		// the original label while waiting for the second click, and what reverts it when time runs out
+		let armed = Rc::new(RefCell::new(None::<(String, hobo::discard::DiscardOnDrop<hobo::futures_signals::CancelableFutureHandle>)>));
+		let disarm = Rc::new(#[clown::clown] move || {
+			let Some((label, _)) = honk!(armed).borrow_mut().take() else { return; };
+			if self.is_dead() { return; }
+			self.set_text(label);
+			self.remove_attr("data-confirming");
+		});
+
+		self.add_on_click(#[clown::clown] move |_| {
+			let armed = &honk!(armed);
+			if armed.borrow().is_some() { honk!(disarm)(); f(); return; }
+
+			let label = self.get_cmp::<web_sys::Node>().text_content().unwrap_or_default();
+			let disarm = Rc::clone(&honk!(disarm));
+			let (handle, fut) = hobo::futures_signals::cancelable_future(async move {
+				async_timer::new_timer(std::time::Duration::from_millis(CONFIRM_ACTION_TIMEOUT_MS.into())).await;
+				disarm();
+			}, Default::default);
+			wasm_bindgen_futures::spawn_local(fut);
+			*armed.borrow_mut() = Some((label, handle));
+			self.set_text(&honk!(confirm_label));
+			self.set_attr("data-confirming", "");
+		});
+		self.add_on_blur(move |_| disarm());
+		self
+	}
+
+	/// Shrinks the font size of a one-line headline until it fits the element's width, picking the largest size between `min_px` and `max_px` that doesn't overflow.
+	///
+	/// Rechecked whenever the element's width changes. The text doesn't wrap, if it doesn't fit even at `min_px` it overflows.
+	#[must_use]
+	fn fit_text(self, min_px: f64, max_px: f64) -> Self where Self: Sized + 'static {
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let Some(style) = dom_element.dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style) else { return self; };
+		style.set_property("white-space", "nowrap").ok();
+
+		let fit = #[clown::clown] move || {
+			let (dom_element, style) = (&honk!(dom_element), &honk!(style));
+			let fits = |size: f64| {
+				style.set_property("font-size", &format!("{size}px")).ok();
+				dom_element.scroll_width() <= dom_element.client_width()
+			};
+			let (mut low, mut high) = (min_px, max_px.max(min_px));
+			if fits(high) { return; }
+			while high - low > FIT_TEXT_PRECISION_PX {
+				let mid = (low + high) / 2.;
+				if fits(mid) { low = mid; } else { high = mid; }
+			}
+			fits(low);
+		};
+		fit();
+
+		// the font size changes the height, so only width changes are worth refitting for
+		let mut width = dom_element.client_width();
+		self.bundle(observe_resize(&dom_element, #[clown::clown] move |_| {
+			let new_width = honk!(dom_element).client_width();
+			if std::mem::replace(&mut width, new_width) != new_width { fit(); }
+		}))
+	}
+
+	/// Keeps a table's header row lined up with and stuck above its scrolling body.
+	///
+	/// The element should contain a `thead` and a `tbody`, either in one table or - when the body has its own scroll container - in two.
+	/// The widths of the cells in the body's first row are copied onto the header's cells, so that row shouldn't use `colspan`.
+	/// Widths are resynced at most once per frame when the body resizes or its rows change,
+	/// and the header's container is scrolled horizontally along with the body's.
+	#[must_use]
+	fn sticky_table_header(self) -> Self where Self: Sized + 'static {
+		let element = self.as_element();
+		let dom_element = self.get_cmp::<web_sys::Element>().clone();
+		let pending = Rc::new(Cell::new(false));
+
+		let sync = Rc::new({
+			let dom_element = dom_element.clone();
+			move || request_frame_coalesced(&pending, #[clown::clown] move || {
+				if element.is_dead() { return; }
+				let dom_element = &honk!(dom_element);
+				let (Ok(Some(header_row)), Ok(Some(body_row))) = (dom_element.query_selector("thead tr"), dom_element.query_selector("tbody tr")) else { return; };
+				if let Some(thead) = header_row.parent_element().and_then(|x| x.dyn_into::<web_sys::HtmlElement>().ok()) {
+					let style = thead.style();
+					style.set_property("position", "sticky").ok();
+					style.set_property("top", "0").ok();
+					style.set_property("z-index", "1").ok();
+				}
+				let (header_cells, body_cells) = (header_row.children(), body_row.children());
+				for i in 0..u32::min(header_cells.length(), body_cells.length()) {
+					let (Some(header_cell), Some(body_cell)) = (header_cells.item(i), body_cells.item(i)) else { continue; };
+					let Some(style) = header_cell.dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style) else { continue; };
+					let width = format!("{}px", body_cell.get_bounding_client_rect().width());
+					style.set_property("box-sizing", "border-box").ok();
+					for property in ["width", "min-width", "max-width"] { style.set_property(property, &width).ok(); }
+				}
+			})
+		});
+		sync();
+
+		// rows get rendered after this is called, so everything is looked up again rather than held onto
+		let options = web_sys::MutationObserverInit::new();
+		options.set_child_list(true);
+		options.set_subtree(true);
+		options.set_character_data(true);
+		self.add_bundle(observe_mutations(&dom_element, &options, #[clown::clown] move |_| honk!(sync)()));
+		self.add_bundle(observe_resize(&dom_element, move |_| sync()));
+
+		// scroll doesn't bubble, but it can be captured on the way down to the body's container
+		self.bundle(listen_capture(&dom_element, "scroll", #[clown::clown] move |e: web_sys::Event| {
+			let Some(container) = e.target().and_then(|x| x.dyn_into::<web_sys::Element>().ok()) else { return; };
+			let dom_element = &honk!(dom_element);
+			let Ok(Some(tbody)) = dom_element.query_selector("tbody") else { return; };
+			let Ok(Some(thead)) = dom_element.query_selector("thead") else { return; };
+			if !container.contains(Some(&tbody)) || container.contains(Some(&thead)) { return; }
+			let Some(header_container) = thead.parent_element().and_then(|x| x.parent_element()) else { return; };
+			header_container.set_scroll_left(container.scroll_left());
+		}))
+	}
+
+	/// Plays an entrance animation when the element scrolls into view (at least 10% of it visible).
+	///
+	/// Does nothing if the user prefers reduced motion, so the element is just shown right away.
+	#[must_use]
+	fn reveal_on_scroll(self, config: RevealConfig) -> Self where Self: Sized + 'static {
+		struct RevealStyleTag;
+		if super::prefers_reduced_motion() { return self; }
+
+		let revealed = hobo::signal::Mutable::new(false);
+		let options = web_sys::IntersectionObserverInit::new();
+		options.set_threshold_f64(0.1);
+		let observer = observe_intersection([&*self.get_cmp::<web_sys::Element>()], &options, #[clown::clown] move |entries| {
+			let revealed = &honk!(revealed);
+			for entry in entries {
+				if entry.is_intersecting() { revealed.set_neq(true); } else if !config.once { revealed.set_neq(false); }
+			}
+		});
+
+		self
+			.bundle(observer)
+			.class_typed_signal::<RevealStyleTag, _, _>(revealed.signal().map(move |x| reveal_properties(config.animation, x)))
+	}
+
+	#[must_use]
+	fn user_select(self, mode: UserSelect) -> Self where Self: Sized {
+		struct UserSelectStyleTag;
+		let value = match mode {
+			UserSelect::Auto => css::user_select::auto,
+			UserSelect::None => css::user_select::none,
+			UserSelect::Text => css::user_select::text,
+			UserSelect::All => css::user_select::all,
+		};
+		self.class_typed::<UserSelectStyleTag>(vec![css::Property::UserSelect(value)])
+	}
+
+	/// Stops text anywhere on the page from getting selected while the element is being dragged, e.g. a slider thumb.
+	///
+	/// Captures the pointer on `pointerdown` and suppresses `selectstart` on the whole document until the capture is lost,
+	/// which happens on `pointerup`/`pointercancel` or when something else takes it.
+	#[must_use]
+	fn prevent_selection_during_drag(self) -> Self where Self: Sized {
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let suppressor = Rc::new(RefCell::new(None::<super::listener::EventListener>));
+		self.add_bundle(listen(&target, "pointerdown", #[clown::clown] move |e: web_sys::PointerEvent| {
+			if !e.is_primary() || e.button() != 0 { return; }
+			if let Some(element) = e.current_target().and_then(|x| x.dyn_into::<web_sys::Element>().ok()) { element.set_pointer_capture(e.pointer_id()).ok(); }
+			if let Ok(Some(selection)) = window().get_selection() { selection.remove_all_ranges().ok(); }
+			*honk!(suppressor).borrow_mut() = Some(listen(&super::document(), "selectstart", |e: web_sys::Event| e.prevent_default()));
+		}));
+		self.add_bundle(listen(&target, "lostpointercapture", move |_: web_sys::Event| { suppressor.borrow_mut().take(); }));
+		self
+	}
+
+	/// Swipe-to-dismiss for cards and toasts - the element follows horizontal drags and, when released past `SWIPE_DISMISS_FRACTION` of its width
+	/// or flung faster than `SWIPE_FLING_VELOCITY`, flies off-screen, fading out. Then `on_dismiss` is called and the element removed.
+	/// Released before that, it springs back.
+	///
+	/// Sets `touch-action: pan-y` so vertical scrolling keeps working on touch screens. If the user prefers reduced motion, both the fly-off and the snap-back are instant.
+	#[must_use]
+	fn swipe_to_dismiss(self, on_dismiss: impl FnOnce() + 'static) -> Self where Self: Sized + 'static {
+		struct SwipeToDismissTag;
+		let element = self.as_element();
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		let Some(style) = self.get_cmp::<web_sys::Element>().dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style) else { return self; };
+		let drag = Rc::new(Cell::new(None::<SwipeDrag>));
+		// where the element is flying off to, once it's been dismissed
+		let fly_off_to = Rc::new(Cell::new(None::<f64>));
+
+		let on_dismiss = Cell::new(Some(on_dismiss));
+		let offset = SpringValue::new(SpringConfig::default(), 0., #[clown::clown] move |x| {
+			if element.is_dead() { return; }
+			let width = element.width().max(1.);
+			style.set_property("transform", &format!("translateX({x}px)")).ok();
+			style.set_property("opacity", &(1. - x.abs() / width).clamp(0., 1.).to_string()).ok();
+			if honk!(fly_off_to).get() != Some(x) { return; }
+			if let Some(on_dismiss) = on_dismiss.take() { on_dismiss(); }
+			if !element.is_dead() { element.remove(); }
+		});
+
+		self.add_bundle(listen(&target, "pointerdown", #[clown::clown] move |e: web_sys::PointerEvent| {
+			if !e.is_primary() || e.button() != 0 || honk!(fly_off_to).get().is_some() { return; }
+			if let Some(element) = e.current_target().and_then(|x| x.dyn_into::<web_sys::Element>().ok()) { element.set_pointer_capture(e.pointer_id()).ok(); }
+			let x = f64::from(e.client_x());
+			honk!(drag).set(Some(SwipeDrag { pointer_id: e.pointer_id(), start_x: x - honk!(offset).value(), last_x: x, last_time: e.time_stamp(), velocity: 0. }));
+		}));
+		self.add_bundle(listen(&target, "pointermove", #[clown::clown] move |e: web_sys::PointerEvent| {
+			let drag = &honk!(drag);
+			let Some(mut current) = drag.get().filter(|x| x.pointer_id == e.pointer_id()) else { return; };
+			let (x, time) = (f64::from(e.client_x()), e.time_stamp());
+			if time > current.last_time { current.velocity = (x - current.last_x) / (time - current.last_time) * 1000.; }
+			current.last_x = x;
+			current.last_time = time;
+			drag.set(Some(current));
+			honk!(offset).set_value(x - current.start_x);
+		}));
+
+		let release = Rc::new(move |e: web_sys::PointerEvent, cancelled: bool| {
+			let Some(current) = drag.get().filter(|x| x.pointer_id == e.pointer_id()) else { return; };
+			drag.set(None);
+			let x = offset.value();
+			let flung = current.velocity.abs() > SWIPE_FLING_VELOCITY && current.velocity.signum() == x.signum();
+			let dismissed = !cancelled && (x.abs() > element.width() * SWIPE_DISMISS_FRACTION || flung);
+			let to = if dismissed { x.signum() * window().inner_width().ok().and_then(|x| x.as_f64()).unwrap_or_default().max(element.width()) } else { 0. };
+			if dismissed { fly_off_to.set(Some(to)); }
+			if super::prefers_reduced_motion() { offset.set_value(to); return; }
+			offset.set_target(to);
+			offset.set_velocity(current.velocity);
+		});
+		self.add_bundle(listen(&target, "pointerup", #[clown::clown] move |e: web_sys::PointerEvent| honk!(release)(e, false)));
+		self.add_bundle(listen(&target, "pointercancel", move |e: web_sys::PointerEvent| release(e, true)));
+		self.class_typed::<SwipeToDismissTag>(vec![css::Property::Raw("touch-action:pan-y;".to_owned())])
+	}
+
+	/// Sets the element's `background-image` to `url` once it comes within `LAZY_BACKGROUND_MARGIN` of the viewport, e.g. for hero sections.
+	///
+	/// The image is preloaded off-DOM first, so the background appears only once it's ready instead of painting in gradually.
+	#[must_use]
+	fn lazy_background(self, url: &str) -> Self where Self: Sized + 'static {
+		struct LazyBackgroundStyleTag;
+		let element = self.as_element();
+		let url = url.to_owned();
+		let observer = Rc::new(RefCell::new(None::<web_sys::IntersectionObserver>));
+		let options = web_sys::IntersectionObserverInit::new();
+		options.set_root_margin(LAZY_BACKGROUND_MARGIN);
+		let guard = observe_intersection([&*self.get_cmp::<web_sys::Element>()], &options, #[clown::clown] move |entries| {
+			if !entries.iter().any(web_sys::IntersectionObserverEntry::is_intersecting) { return; }
+			let Some(observer) = honk!(observer).borrow_mut().take() else { return; };
+			observer.disconnect();
+
+			let url = honk!(url).clone();
+			element.spawn(async move {
+				if let Ok(image) = web_sys::HtmlImageElement::new() {
+					image.set_src(&url);
+					if let Err(e) = wasm_bindgen_futures::JsFuture::from(image.decode()).await { log::warn!("failed to preload background {url}: {e:?}"); }
+				}
+				element.set_class_typed::<LazyBackgroundStyleTag>(vec![css::background_image::url(super::escape_css_url(&url)).into()]);
+			});
+		});
+		*observer.borrow_mut() = Some(guard.observer().clone());
+		self.bundle(guard)
+	}
+
+	/// Shows `format(from)`, then counts up to `format(to)` over `duration_ms` (easing out) the first time the element scrolls into view.
+	///
+	/// Jumps straight to the final value if the user prefers reduced motion.
+	#[must_use]
+	fn count_up(self, from: f64, to: f64, duration_ms: f64, format: impl Fn(f64) -> String + 'static) -> Self where Self: Sized + Copy + 'static {
+		let format = Rc::new(format);
+		let started = Rc::new(Cell::new(false));
+		let element = self.as_element();
+		let observer = observe_intersection([&*self.get_cmp::<web_sys::Element>()], &web_sys::IntersectionObserverInit::new(), #[clown::clown] move |entries| {
+			if !entries.iter().any(web_sys::IntersectionObserverEntry::is_intersecting) || honk!(started).replace(true) { return; }
+			let format = Rc::clone(&honk!(format));
+			if super::prefers_reduced_motion() || duration_ms <= 0. { element.set_text(format(to)); return; }
+
+			let mut elapsed = 0.;
+			super::animation(move |delta_t| {
+				if element.is_dead() { return false; }
+				elapsed += delta_t;
+				let t = f64::min(elapsed / duration_ms, 1.);
+				let eased = 1. - (1. - t).powi(3);
+				element.set_text(format(from + (to - from) * eased));
+				t < 1.
+			});
+		});
+
+		self.text(format(from)).bundle(observer)
+	}
+
+	/// Saves the element's inline style, so a transient effect can change it freely and `restore_style` can put it back exactly.
+	fn snapshot_style(&self) -> StyleSnapshot {
+		StyleSnapshot(self.get_cmp::<web_sys::Element>().get_attribute(web_str::style()))
+	}
+
+	/// Reverts the inline style to a `snapshot_style`, including removing it if there was none.
+	fn restore_style(&self, snapshot: &StyleSnapshot) {
+		let element = self.get_cmp::<web_sys::Element>();
+		match &snapshot.0 {
+			Some(style) => { element.set_attribute(web_str::style(), style).ok(); },
+			None => { element.remove_attribute(web_str::style()).ok(); },
+		}
+	}
+
+	/// Draws attention to the element with an outline in `color` that fades out over `duration_ms`, e.g. after jumping to a comment.
+	///
+	/// The inline style is restored afterwards, flashing again while a flash is still going restarts it.
+	/// If the user prefers reduced motion, the outline doesn't fade but just stays for at most `FLASH_REDUCED_MOTION_MS`.
+	#[must_use]
+	fn flash(self, color: css::Color, duration_ms: f64) -> Self where Self: Sized + Copy + 'static {
+		let element = self.as_element();
+		let previous = element.try_get_cmp::<Flashing>().map(|x| (x.snapshot.clone(), x.generation));
+		let (snapshot, generation) = previous.map_or_else(|| (self.snapshot_style(), 0), |(snapshot, generation)| (snapshot, generation + 1));
+		element.add_component(Flashing { snapshot, generation });
+		let Some(style) = self.get_cmp::<web_sys::Element>().dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style) else { return self; };
+
+		let set_outline = move |alpha: f64| {
+			let alpha = f64::from(color.a) / 255. * alpha;
+			style.set_property("outline", &format!("{FLASH_OUTLINE_PX}px solid rgba({}, {}, {}, {alpha})", color.r, color.g, color.b)).ok();
+			style.set_property("outline-offset", &format!("{FLASH_OUTLINE_PX}px")).ok();
+		};
+		let is_current = move || !element.is_dead() && element.try_get_cmp::<Flashing>().is_some_and(|x| x.generation == generation);
+		let finish = move || {
+			if !is_current() { return; }
+			let snapshot = element.get_cmp::<Flashing>().snapshot.clone();
+			element.restore_style(&snapshot);
+			element.remove_cmp::<Flashing>();
+		};
+
+		set_outline(1.);
+		if super::prefers_reduced_motion() || duration_ms <= 0. {
+			let duration = std::time::Duration::from_secs_f64(duration_ms.clamp(0., FLASH_REDUCED_MOTION_MS) / 1000.);
+			element.spawn(async move { async_timer::new_timer(duration).await; finish(); });
+			return self;
+		}
+
+		let mut elapsed = 0.;
+		super::animation(move |delta_t| {
+			if !is_current() { return false; }
+			elapsed += delta_t;
+			let t = f64::min(elapsed / duration_ms, 1.);
+			// stays bright for a bit, then fades out quicker and quicker
+			set_outline(1. - t.powi(3));
+			if t >= 1. { finish(); }
+			t < 1.
+		});
+		self
+	}
+
+	/// Smoothly scrolls the element into the middle of the viewport and `flash`es it.
+	#[must_use]
+	fn scroll_into_view_and_flash(self, color: css::Color, duration_ms: f64) -> Self where Self: Sized + Copy + 'static {
+		let options = web_sys::ScrollIntoViewOptions::new();
+		options.set_behavior(if super::prefers_reduced_motion() { web_sys::ScrollBehavior::Instant } else { web_sys::ScrollBehavior::Smooth });
+		options.set_block(web_sys::ScrollLogicalPosition::Center);
+		self.get_cmp::<web_sys::Element>().scroll_into_view_with_scroll_into_view_options(&options);
+		self.flash(color, duration_ms)
+	}
+
+	/// Stores the element's current scroll offset under `key` (e.g. the current route), for `restore_scroll` to reapply later.
+	fn remember_scroll(&self, key: &str) {
+		let element = self.get_cmp::<web_sys::Element>();
+		let offset = (element.scroll_left() as f64, element.scroll_top() as f64);
+		SCROLL_POSITIONS.with(|x| x.borrow_mut().insert(key.to_owned(), offset));
+	}
+
+	/// Reapplies the scroll offset stored by `remember_scroll` under `key`, after the next reflow so freshly rendered content is accounted for.
+	///
+	/// If the content got shorter in the meantime, scrolls as far as it can.
+	fn restore_scroll(&self, key: &str) {
+		let Some((left, top)) = SCROLL_POSITIONS.with(|x| x.borrow().get(key).copied()) else { return; };
+		let element = self.as_element();
+		element.set_on_next_flow(move || {
+			if element.is_dead() { return; }
+			let element = element.get_cmp::<web_sys::Element>();
+			let max_left = f64::max(0., (element.scroll_width() - element.client_width()) as f64);
+			let max_top = f64::max(0., (element.scroll_height() - element.client_height()) as f64);
+			element.scroll_to_with_x_and_y(left.min(max_left), top.min(max_top));
+		});
+	}
+
+	/// Dev instrumentation: measures the time from this call until the next flow,
+	/// so put it first in the builder chain to cover constructing the subtree along with its first style and layout.
+	///
+	/// The duration is logged with `log::debug!` and recorded as a `performance.measure` named `label`, which shows up in devtools' performance panel.
+	/// Does nothing unless the `timing` feature is enabled, so calls can be left in place.
+	#[must_use]
+	fn with_timing(self, label: &str) -> Self where Self: Sized + Copy + 'static {
+		#[cfg(feature = "timing")] {
+			let Some(performance) = window().performance() else { return self; };
+			let start_mark = format!("{label}:start");
+			performance.mark(&start_mark).ok();
+			let start = performance.now();
+			let label = label.to_owned();
+			self.set_on_next_flow(move || {
+				let duration = performance.now() - start;
+				performance.measure_with_start_mark(&label, &start_mark).ok();
+				performance.clear_marks_with_mark_name(&start_mark);
+				log::debug!("{label} took {duration:.2}ms");
+			});
+		}
+		#[cfg(not(feature = "timing"))] let _ = label;
+		self
+	}
+
+	/// Wraps each occurrence of `query` within the element's text in a `<mark>`, leaving child elements as they are.
+	///
+	/// Marks from a previous call are removed first, so this can be called again whenever the query changes - an empty query just clears them.
+	/// Matches don't overlap (scanning resumes after each one) and ones spanning element boundaries are skipped.
+	/// `case_insensitive` only folds ASCII letters.
+	#[must_use]
+	fn highlight_matches(self, query: &str, case_insensitive: bool) -> Self where Self: Sized {
+		let element = self.get_cmp::<web_sys::Element>().clone();
+		clear_highlights(&element);
+		if query.is_empty() { return self; }
+
+		let fold = |x: &str| if case_insensitive { x.to_ascii_lowercase() } else { x.to_owned() };
+		let query = fold(query);
+		let document = super::document();
+		let mut text_nodes = Vec::new();
+		collect_text_nodes(&element, &mut text_nodes);
+
+		for node in text_nodes {
+			let text = node.data();
+			let haystack = fold(&text);
+			let mut matches = haystack.match_indices(&query).map(|(i, _)| i).peekable();
+			if matches.peek().is_none() { continue; }
+
+			let Some(parent) = node.parent_node() else { continue; };
+			let fragment = document.create_document_fragment();
+			let mut last = 0;
+			for start in matches {
+				let end = start + query.len();
+				if start > last { fragment.append_child(&document.create_text_node(&text[last..start])).ok(); }
+				let mark = document.create_element("mark").unwrap();
+				mark.set_attribute(HIGHLIGHT_ATTR, "").ok();
+				mark.set_text_content(Some(&text[start..end]));
+				fragment.append_child(&mark).ok();
+				last = end;
+			}
+			if last < text.len() { fragment.append_child(&document.create_text_node(&text[last..])).ok(); }
+			parent.replace_child(&fragment, &node).ok();
+		}
+		self
 	}
 
 	fn scroll_to_start(&self) {
-		self.get_cmp::<web_sys::HtmlDivElement>().scroll_to_with_x_and_y(0., 0.);
+		self.get_cmp::<web_sys::Element>().scroll_to_with_x_and_y(0., 0.);
 	}
 
 	fn scroll_to_end(&self) {
-		let ele = self.get_cmp::<web_sys::HtmlDivElement>();
+		let ele = self.get_cmp::<web_sys::Element>();
 		ele.scroll_to_with_x_and_y(0., ele.scroll_height() as f64);
 	}
+
+	/// Animated `scroll_to_start`, e.g. for jumping back to the top of a feed. Instant if the user prefers reduced motion.
+	fn scroll_to_start_smooth(&self) {
+		self.get_cmp::<web_sys::Element>().scroll_to_with_scroll_to_options(&smooth_scroll_to(0.));
+	}
+
+	/// Animated `scroll_to_end`, e.g. when appending a message to a chat log. Instant if the user prefers reduced motion.
+	fn scroll_to_end_smooth(&self) {
+		let ele = self.get_cmp::<web_sys::Element>();
+		ele.scroll_to_with_scroll_to_options(&smooth_scroll_to(ele.scroll_height() as f64));
+	}
 }
 
 impl<T: AsElement> AsElementExt for T {}
@@ -1,7 +1,9 @@
 #![expect(clippy::type_complexity)]
 
+use std::{cell::RefCell, rc::Rc};
 use hobo::prelude::*;
 use hobo::{signal_map::{MapDiff, MutableBTreeMap, SignalMapExt}, signal::SignalExt};
+use crate::{animation_for_duration, prefers_reduced_motion, AnimationHandle};
 
 #[derive(Clone)]
 pub struct ItemMapping<K, V> where
@@ -48,6 +50,8 @@ pub struct ChildrenDiffConfig<K, V, E, Insert, OnChange, OnRemove, OnUpdate> {
 	on_change: OnChange,
 	on_remove: OnRemove,
 	on_update: OnUpdate,
+	preserve_scroll: bool,
+	move_duration_ms: Option<f64>,
 	_pd: std::marker::PhantomData<(K, V, E)>,
 }
 
@@ -56,6 +60,8 @@ pub struct ChildrenDiffConfigBuilder<K, V, E, Insert, OnChange, OnRemove, OnUpda
 	on_change: OnChange,
 	on_remove: OnRemove,
 	on_update: OnUpdate,
+	preserve_scroll: bool,
+	move_duration_ms: Option<f64>,
 	_pd: std::marker::PhantomData<(K, V, E)>,
 }
 
@@ -65,6 +71,8 @@ impl<E, Insert> ChildrenDiffConfig<(), (), E, Insert, fn(), fn(&()), fn(&(), &()
 		on_change: move || {},
 		on_remove: move |_| {},
 		on_update: move |_, _| {},
+		preserve_scroll: false,
+		move_duration_ms: None,
 		_pd: std::marker::PhantomData,
 	} }
 }
@@ -77,6 +85,12 @@ impl<K, V, E, Insert, OnChange, OnRemove, OnUpdate> ChildrenDiffConfigBuilder<K,
 	OnUpdate: FnMut(&K, &V) + 'static,
 {
 	#[must_use] pub fn insert(mut self, f: Insert) -> Self { self.insert = Some(f); self }
+	/// Keeps the topmost visible item in place when the contents change, e.g. when switching between overlapping filtered datasets.
+	/// Assumes the element getting the children is the scroll container.
+	#[must_use] pub fn preserve_scroll_on_update(mut self, x: bool) -> Self { self.preserve_scroll = x; self }
+	/// Slides items that got shifted by others being added or removed into their new place over `duration_ms` (FLIP), instead of having them jump.
+	/// Skipped if the user prefers reduced motion.
+	#[must_use] pub fn animate_moves(mut self, duration_ms: f64) -> Self { self.move_duration_ms = Some(duration_ms); self }
 	pub fn on_change<NewOnChange>(self, f: NewOnChange) -> ChildrenDiffConfigBuilder<K, V, E, Insert, NewOnChange, OnRemove, OnUpdate> where
 		NewOnChange: FnMut() + 'static,
 	{ ChildrenDiffConfigBuilder {
@@ -84,6 +98,8 @@ impl<K, V, E, Insert, OnChange, OnRemove, OnUpdate> ChildrenDiffConfigBuilder<K,
 		on_change: f,
 		on_remove: self.on_remove,
 		on_update: self.on_update,
+		preserve_scroll: self.preserve_scroll,
+		move_duration_ms: self.move_duration_ms,
 		_pd: std::marker::PhantomData,
 	} }
 	pub fn on_remove<NewOnRemove>(self, f: NewOnRemove) -> ChildrenDiffConfigBuilder<K, V, E, Insert, OnChange, NewOnRemove, OnUpdate> where
@@ -93,6 +109,8 @@ impl<K, V, E, Insert, OnChange, OnRemove, OnUpdate> ChildrenDiffConfigBuilder<K,
 		on_change: self.on_change,
 		on_remove: f,
 		on_update: self.on_update,
+		preserve_scroll: self.preserve_scroll,
+		move_duration_ms: self.move_duration_ms,
 		_pd: std::marker::PhantomData,
 	} }
 	pub fn on_update<NewOnUpdate>(self, f: NewOnUpdate) -> ChildrenDiffConfigBuilder<K, V, E, Insert, OnChange, OnRemove, NewOnUpdate> where
@@ -102,6 +120,8 @@ impl<K, V, E, Insert, OnChange, OnRemove, OnUpdate> ChildrenDiffConfigBuilder<K,
 		on_change: self.on_change,
 		on_remove: self.on_remove,
 		on_update: f,
+		preserve_scroll: self.preserve_scroll,
+		move_duration_ms: self.move_duration_ms,
 		_pd: std::marker::PhantomData,
 	} }
 
@@ -111,11 +131,96 @@ impl<K, V, E, Insert, OnChange, OnRemove, OnUpdate> ChildrenDiffConfigBuilder<K,
 			on_change: self.on_change,
 			on_remove: self.on_remove,
 			on_update: self.on_update,
+			preserve_scroll: self.preserve_scroll,
+			move_duration_ms: self.move_duration_ms,
 			_pd: std::marker::PhantomData,
 		}
 	}
 }
 
+// the topmost visible item and how far it was from the top of the container, or just the scroll offset if nothing was visible
+struct ScrollAnchor<K> {
+	key: Option<K>,
+	offset: f64,
+	scroll_top: f64,
+}
+
+fn capture_scroll_anchor<K: Clone>(container: &web_sys::Element, items: &std::collections::BTreeMap<K, hobo::Element>) -> ScrollAnchor<K> {
+	let container_top = container.get_bounding_client_rect().top();
+	let topmost = items.iter()
+		.map(|(key, element)| (key, element.get_cmp::<web_sys::Element>().get_bounding_client_rect()))
+		.filter(|(_, rect)| rect.bottom() > container_top)
+		.min_by(|a, b| a.1.top().total_cmp(&b.1.top()));
+	ScrollAnchor {
+		key: topmost.as_ref().map(|(key, _)| (*key).clone()),
+		offset: topmost.map_or(0., |(_, rect)| rect.top() - container_top),
+		scroll_top: f64::from(container.scroll_top()),
+	}
+}
+
+fn restore_scroll_anchor<K: Ord>(container: &web_sys::Element, items: &std::collections::BTreeMap<K, hobo::Element>, anchor: ScrollAnchor<K>) {
+	let Some(element) = anchor.key.and_then(|key| items.get(&key).copied()) else {
+		container.set_scroll_top(anchor.scroll_top as i32);
+		return;
+	};
+	let offset = element.get_cmp::<web_sys::Element>().get_bounding_client_rect().top() - container.get_bounding_client_rect().top();
+	container.set_scroll_top((f64::from(container.scroll_top()) + offset - anchor.offset).round() as i32);
+}
+
+fn item_positions<K: Ord + Clone>(items: &std::collections::BTreeMap<K, hobo::Element>) -> std::collections::BTreeMap<K, (f64, f64)> {
+	items.iter()
+		.map(|(key, element)| {
+			let rect = element.get_cmp::<web_sys::Element>().get_bounding_client_rect();
+			(key.clone(), (rect.left(), rect.top()))
+		})
+		.collect()
+}
+
+// an item's ongoing move animation and its inline transform from before it started
+struct MoveAnimation {
+	handle: AnimationHandle,
+	transform: String,
+}
+
+type MoveAnimations<K> = Rc<RefCell<std::collections::BTreeMap<K, MoveAnimation>>>;
+
+fn style_of(element: hobo::Element) -> Option<web_sys::CssStyleDeclaration> {
+	element.get_cmp::<web_sys::Element>().dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style)
+}
+
+// the "invert" and "play" of FLIP - offsets each item that moved back to where it was `before` and animates the offset away
+fn animate_moves<K: Ord + Clone + 'static>(items: &std::collections::BTreeMap<K, hobo::Element>, before: &std::collections::BTreeMap<K, (f64, f64)>, running: &MoveAnimations<K>, duration_ms: f64) {
+	// settle interrupted animations first so their offsets don't skew the new positions
+	let interrupted = std::mem::take(&mut *running.borrow_mut());
+	for (key, animation) in interrupted {
+		animation.handle.cancel();
+		if let Some(style) = items.get(&key).copied().and_then(style_of) { style.set_property("transform", &animation.transform).ok(); }
+	}
+
+	for (key, &element) in items {
+		let (Some(&(old_x, old_y)), Some(style)) = (before.get(key), style_of(element)) else { continue; };
+		let rect = element.get_cmp::<web_sys::Element>().get_bounding_client_rect();
+		let (dx, dy) = (old_x - rect.left(), old_y - rect.top());
+		if dx.abs() < 0.5 && dy.abs() < 0.5 { continue; }
+
+		let transform = style.get_property_value("transform").unwrap_or_default();
+		let offset = { let transform = transform.clone(); move |remaining: f64| format!("translate({}px, {}px) {transform}", dx * remaining, dy * remaining) };
+		style.set_property("transform", &offset(1.)).ok();
+		let handle = animation_for_duration(duration_ms, {
+			let (running, key, transform) = (Rc::clone(running), key.clone(), transform.clone());
+			move |t| {
+				if t < 1. {
+					style.set_property("transform", &offset((1. - t).powi(3))).ok();
+				} else {
+					style.set_property("transform", &transform).ok();
+					running.borrow_mut().remove(&key);
+				}
+			}
+		});
+		running.borrow_mut().insert(key.clone(), MoveAnimation { handle, transform });
+	}
+}
+
 pub struct ChildrenDiff<K, V> where
 	K: Ord + Clone + std::hash::Hash + 'static,
 	V: 'static,
@@ -170,83 +275,112 @@ pub trait ChildrenDiffElementExt: AsElement {
 		OnRemove: FnMut(&K) + 'static,
 		OnUpdate: FnMut(&K, &V) + 'static,
 	{
-		let ChildrenDiffConfig { mut insert, mut on_change, mut on_remove, mut on_update, .. } = config.build();
+		let ChildrenDiffConfig { mut insert, mut on_change, mut on_remove, mut on_update, preserve_scroll, move_duration_ms, .. } = config.build();
 		let mutable = MutableBTreeMap::<K, V>::new();
+		let scroll_anchor = Rc::new(RefCell::new(None::<ScrollAnchor<K>>));
+		let positions_before = Rc::new(RefCell::new(None::<std::collections::BTreeMap<K, (f64, f64)>>));
+		let move_animations = MoveAnimations::<K>::default();
 		self
-			.component(mutable.signal_map_cloned().subscribe(move |diff| match diff {
-				MapDiff::Insert { key, value } => {
-					{
-						let element = insert(&key, ItemMapping::new(self.as_element(), key.clone(), value)).as_element();
-						self.add_child(element);
-
-						let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
-						children_diff.unprocessed_ids.remove(&key);
-						children_diff.items.insert(key, element);
-						if !children_diff.unprocessed_ids.is_empty() { return; }
-					}
-
-					on_change();
-				},
-				MapDiff::Remove { key } => {
-					{
-						let element = self.get_cmp_mut::<ChildrenDiff<K, V>>().items.remove(&key).unwrap();
-						element.remove();
-						on_remove(&key);
-
-						let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
-						children_diff.unprocessed_ids.remove(&key);
-						if !children_diff.unprocessed_ids.is_empty() { return; }
-					}
-
-					on_change();
-				},
-				MapDiff::Update { key, value } => {
-					{
-						on_update(&key, &value);
-
-						let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
-						children_diff.unprocessed_ids.remove(&key);
-						if !children_diff.unprocessed_ids.is_empty() { return; }
-					}
-
-					on_change();
-				},
-				MapDiff::Clear { } => {
-					{
-						let items = std::mem::take(&mut self.get_cmp_mut::<ChildrenDiff<K, V>>().items);
-						for (key, element) in items {
+			.component(mutable.signal_map_cloned().subscribe(move |diff| {
+				// captured before the first of a batch of diffs and restored once they're all through, before the next paint
+				if preserve_scroll && scroll_anchor.borrow().is_none() {
+					let container = self.get_cmp::<web_sys::Element>().clone();
+					*scroll_anchor.borrow_mut() = Some(capture_scroll_anchor(&container, &self.get_cmp::<ChildrenDiff<K, V>>().items));
+					let scroll_anchor = Rc::clone(&scroll_anchor);
+					super::window().request_animation_frame(Closure::once_into_js(move || {
+						let Some(anchor) = scroll_anchor.borrow_mut().take() else { return; };
+						if self.is_dead() { return; }
+						restore_scroll_anchor(&container, &self.get_cmp::<ChildrenDiff<K, V>>().items, anchor);
+					}).unchecked_ref()).unwrap();
+				}
+
+				// same for positions, the moves are animated after scrolling got restored so they aren't thrown off by it
+				if let Some(duration_ms) = move_duration_ms.filter(|_| positions_before.borrow().is_none() && !prefers_reduced_motion()) {
+					*positions_before.borrow_mut() = Some(item_positions(&self.get_cmp::<ChildrenDiff<K, V>>().items));
+					let positions_before = Rc::clone(&positions_before);
+					let move_animations = Rc::clone(&move_animations);
+					super::window().request_animation_frame(Closure::once_into_js(move || {
+						let Some(before) = positions_before.borrow_mut().take() else { return; };
+						if self.is_dead() { return; }
+						animate_moves(&self.get_cmp::<ChildrenDiff<K, V>>().items, &before, &move_animations, duration_ms);
+					}).unchecked_ref()).unwrap();
+				}
+
+				match diff {
+					MapDiff::Insert { key, value } => {
+						{
+							let element = insert(&key, ItemMapping::new(self.as_element(), key.clone(), value)).as_element();
+							self.add_child(element);
+
+							let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
+							children_diff.unprocessed_ids.remove(&key);
+							children_diff.items.insert(key, element);
+							if !children_diff.unprocessed_ids.is_empty() { return; }
+						}
+
+						on_change();
+					},
+					MapDiff::Remove { key } => {
+						{
+							let element = self.get_cmp_mut::<ChildrenDiff<K, V>>().items.remove(&key).unwrap();
 							element.remove();
 							on_remove(&key);
+
+							let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
+							children_diff.unprocessed_ids.remove(&key);
+							if !children_diff.unprocessed_ids.is_empty() { return; }
 						}
 
-						let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
-						children_diff.unprocessed_ids.clear();
-					}
+						on_change();
+					},
+					MapDiff::Update { key, value } => {
+						{
+							on_update(&key, &value);
 
-					on_change();
-				},
-				MapDiff::Replace { entries } => {
-					{
-						let items = std::mem::take(&mut self.get_cmp_mut::<ChildrenDiff<K, V>>().items);
-						for (key, element) in items {
-							element.remove();
-							on_remove(&key);
+							let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
+							children_diff.unprocessed_ids.remove(&key);
+							if !children_diff.unprocessed_ids.is_empty() { return; }
 						}
 
-						self.get_cmp_mut::<ChildrenDiff<K, V>>().unprocessed_ids.clear();
+						on_change();
+					},
+					MapDiff::Clear { } => {
+						{
+							let items = std::mem::take(&mut self.get_cmp_mut::<ChildrenDiff<K, V>>().items);
+							for (key, element) in items {
+								element.remove();
+								on_remove(&key);
+							}
 
-						let mut items = std::collections::BTreeMap::<K, hobo::Element>::new();
-						for (key, value) in entries {
-							let element = insert(&key, ItemMapping::new(self.as_element(), key.clone(), value)).as_element();
-							self.add_child(element);
-							items.insert(key.clone(), element);
+							let mut children_diff = self.get_cmp_mut::<ChildrenDiff<K, V>>();
+							children_diff.unprocessed_ids.clear();
 						}
 
-						self.get_cmp_mut::<ChildrenDiff<K, V>>().items = items;
-					}
+						on_change();
+					},
+					MapDiff::Replace { entries } => {
+						{
+							let items = std::mem::take(&mut self.get_cmp_mut::<ChildrenDiff<K, V>>().items);
+							for (key, element) in items {
+								element.remove();
+								on_remove(&key);
+							}
+
+							self.get_cmp_mut::<ChildrenDiff<K, V>>().unprocessed_ids.clear();
+
+							let mut items = std::collections::BTreeMap::<K, hobo::Element>::new();
+							for (key, value) in entries {
+								let element = insert(&key, ItemMapping::new(self.as_element(), key.clone(), value)).as_element();
+								self.add_child(element);
+								items.insert(key.clone(), element);
+							}
+
+							self.get_cmp_mut::<ChildrenDiff<K, V>>().items = items;
+						}
 
-					on_change();
-				},
+						on_change();
+					},
+				}
 			}))
 			.component(ChildrenDiff { mutable, element: self.as_element(), items: Default::default(), unprocessed_ids: Default::default() })
 	}
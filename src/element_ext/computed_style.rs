@@ -0,0 +1,188 @@
+use hobo::prelude::*;
+
+// computed lengths come back in px, but percentages and font-relative units show up for a few properties
+fn unit(value: &str) -> Option<css::Unit> {
+	let number = |suffix: &str| value.strip_suffix(suffix)?.parse::<f32>().ok().filter(|x| x.is_finite());
+	if value == "0" { return Some(css::Unit::Zero); }
+	if let Some(x) = number("px") { return Some(css::Unit::px(x)); }
+	if let Some(x) = number("%") { return Some(css::Unit::pct(x)); }
+	if let Some(x) = number("rem") { return Some(css::Unit::rem(x)); }
+	if let Some(x) = number("em") { return Some(css::Unit::em(x)); }
+	if let Some(x) = number("vw") { return Some(css::Unit::vw(x)); }
+	if let Some(x) = number("vh") { return Some(css::Unit::vh(x)); }
+	None
+}
+
+// `rgb(1, 2, 3)`, `rgba(1, 2, 3, 0.5)` or `rgb(1 2 3 / 0.5)`, which is what browsers serialize computed colors as
+fn color(value: &str) -> Option<css::ColorValue> {
+	if value == "transparent" { return Some(css::ColorValue::Rgba(css::Color { r: 0, g: 0, b: 0, a: 0 })); }
+	let arguments = value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb("))?.strip_suffix(')')?;
+	let mut parts = arguments.split([',', '/', ' ']).map(str::trim).filter(|x| !x.is_empty());
+	let mut channel = || parts.next()?.parse::<f32>().ok().map(|x| x.round().clamp(0., 255.) as u8);
+	let (r, g, b) = (channel()?, channel()?, channel()?);
+	let a = match parts.next() {
+		None => 255,
+		Some(x) => match x.strip_suffix('%') {
+			Some(x) => (x.parse::<f32>().ok()? / 100. * 255.).round().clamp(0., 255.) as u8,
+			None => (x.parse::<f32>().ok()? * 255.).round().clamp(0., 255.) as u8,
+		},
+	};
+	if parts.next().is_some() { return None; }
+	Some(css::ColorValue::Rgba(css::Color { r, g, b, a }))
+}
+
+fn dimension(value: &str) -> Option<css::Dimension> {
+	match value {
+		"auto" => Some(css::Dimension::Auto),
+		"none" => Some(css::Dimension::None),
+		"max-content" => Some(css::Dimension::MaxContent),
+		"min-content" => Some(css::Dimension::MinContent),
+		value => unit(value).map(css::Dimension::Some),
+	}
+}
+
+fn position_offset(value: &str) -> Option<css::PositionOffset> {
+	if value == "auto" { Some(css::PositionOffset::Auto) } else { unit(value).map(css::PositionOffset::Some) }
+}
+
+fn margin(value: &str) -> Option<css::Margin> {
+	if value == "auto" { Some(css::Margin::Auto) } else { unit(value).map(css::Margin::Some) }
+}
+
+fn unit_value(value: &str) -> Option<css::UnitValue> { unit(value).map(css::UnitValue::Unit) }
+
+fn border_width(value: &str) -> Option<css::BorderWidth> { unit(value).map(css::BorderWidth::Some) }
+
+// the keyword among `candidates` that serializes to `value`, so the keywords don't have to be spelled out a second time
+fn keyword<T: std::fmt::Display>(value: &str, candidates: impl IntoIterator<Item = T>) -> Option<T> {
+	candidates.into_iter().find(|x| {
+		let css = x.to_string();
+		css.strip_suffix(';').and_then(|x| x.split_once(':')).map_or(css.as_str(), |(_, x)| x) == value
+	})
+}
+
+fn border_style(value: &str) -> Option<css::BorderStyle> {
+	use css::BorderStyle as S;
+	keyword(value, [S::None, S::Hidden, S::Dotted, S::Dashed, S::Solid, S::Double, S::Groove, S::Ridge, S::Inset, S::Outset])
+}
+
+/// The typed `css::Property` for a computed `value` of the property `name`, `None` if it's not one of the supported properties or values.
+pub(super) fn parse_property(name: &str, value: &str) -> Option<css::Property> {
+	use css::Property as P;
+	let value = value.trim();
+	Some(match name {
+		"color" => P::Color(color(value)?),
+		"background-color" => P::BackgroundColor(color(value)?),
+		"border-top-color" => P::BorderTopColor(color(value)?),
+		"border-right-color" => P::BorderRightColor(color(value)?),
+		"border-bottom-color" => P::BorderBottomColor(color(value)?),
+		"border-left-color" => P::BorderLeftColor(color(value)?),
+		"outline-color" => P::OutlineColor(color(value)?),
+		"text-decoration-color" => P::TextDecorationColor(color(value)?),
+		"fill" => P::Fill(color(value)?),
+		"stroke" => P::Stroke(color(value)?),
+
+		"width" => P::Width(dimension(value)?),
+		"height" => P::Height(dimension(value)?),
+		"min-width" => P::MinWidth(dimension(value)?),
+		"max-width" => P::MaxWidth(dimension(value)?),
+		"min-height" => P::MinHeight(dimension(value)?),
+		"max-height" => P::MaxHeight(dimension(value)?),
+
+		"top" => P::Top(position_offset(value)?),
+		"right" => P::Right(position_offset(value)?),
+		"bottom" => P::Bottom(position_offset(value)?),
+		"left" => P::Left(position_offset(value)?),
+
+		"margin-top" => P::MarginTop(margin(value)?),
+		"margin-right" => P::MarginRight(margin(value)?),
+		"margin-bottom" => P::MarginBottom(margin(value)?),
+		"margin-left" => P::MarginLeft(margin(value)?),
+		"padding-top" => P::PaddingTop(unit_value(value)?),
+		"padding-right" => P::PaddingRight(unit_value(value)?),
+		"padding-bottom" => P::PaddingBottom(unit_value(value)?),
+		"padding-left" => P::PaddingLeft(unit_value(value)?),
+
+		"border-top-width" => P::BorderTopWidth(border_width(value)?),
+		"border-right-width" => P::BorderRightWidth(border_width(value)?),
+		"border-bottom-width" => P::BorderBottomWidth(border_width(value)?),
+		"border-left-width" => P::BorderLeftWidth(border_width(value)?),
+		"border-top-style" => P::BorderTopStyle(border_style(value)?),
+		"border-right-style" => P::BorderRightStyle(border_style(value)?),
+		"border-bottom-style" => P::BorderBottomStyle(border_style(value)?),
+		"border-left-style" => P::BorderLeftStyle(border_style(value)?),
+		"border-top-left-radius" => P::BorderTopLeftRadius(unit_value(value)?),
+		"border-top-right-radius" => P::BorderTopRightRadius(unit_value(value)?),
+		"border-bottom-left-radius" => P::BorderBottomLeftRadius(unit_value(value)?),
+		"border-bottom-right-radius" => P::BorderBottomRightRadius(unit_value(value)?),
+		"text-indent" => P::TextIndent(unit_value(value)?),
+		"outline-offset" => P::OutlineOffset(unit_value(value)?),
+		"stroke-width" => P::StrokeWidth(unit_value(value)?),
+
+		"font-size" => P::FontSize(css::font_size::Some(unit(value)?)),
+		"font-weight" => P::FontWeight(css::font_weight::Number(value.parse().ok()?)),
+		"font-style" => P::FontStyle(keyword(value, [css::font_style::normal, css::font_style::italic, css::font_style::oblique])?),
+		"line-height" => P::LineHeight(if value == "normal" { css::line_height::normal } else { css::line_height::Some(unit(value)?) }),
+		"letter-spacing" => P::LetterSpacing(if value == "normal" { css::letter_spacing::normal } else { css::letter_spacing::Some(unit(value)?) }),
+		"word-spacing" => P::WordSpacing(if value == "normal" { css::word_spacing::normal } else { css::word_spacing::Some(unit(value)?) }),
+		"opacity" => P::Opacity(css::opacity::Number(css::units::F32::new(value.parse().ok()?).ok()?)),
+		"z-index" => P::ZIndex(if value == "auto" { css::z_index::auto } else { css::z_index::Number(value.parse().ok()?) }),
+
+		"display" => P::Display(keyword(value, {
+			use css::display::*;
+			[block, none, inline, inline_block, flex, inline_flex, grid, inline_grid, flow_root, contents, table, table_row, table_row_group,
+				table_header_group, table_footer_group, table_cell, table_column_group, table_column, table_caption, list_item]
+		})?),
+		"position" => P::Position(keyword(value, { use css::position::*; [r#static, absolute, fixed, relative, sticky] })?),
+		"visibility" => P::Visibility(keyword(value, { use css::visibility::*; [visible, hidden, collapse] })?),
+		"box-sizing" => P::BoxSizing(keyword(value, { use css::box_sizing::*; [content_box, border_box] })?),
+		"overflow-x" => P::OverflowX(keyword(value, { use css::overflow_x::*; [visible, hidden, scroll, auto] })?),
+		"overflow-y" => P::OverflowY(keyword(value, { use css::overflow_y::*; [visible, hidden, scroll, auto] })?),
+		"float" => P::Float(keyword(value, { use css::float::*; [none, left, right, inline_start, inline_end] })?),
+		"clear" => P::Clear(keyword(value, { use css::clear::*; [none, left, right, inline_start, inline_end, both] })?),
+		"pointer-events" => P::PointerEvents(keyword(value, { use css::pointer_events::*; [auto, none] })?),
+		"object-fit" => P::ObjectFit(keyword(value, { use css::object_fit::*; [fill, contain, cover, scale_down, none] })?),
+		"white-space" => P::WhiteSpace(keyword(value, { use css::white_space::*; [normal, nowrap, pre, pre_line, pre_wrap, break_spaces] })?),
+		"text-align" => P::TextAlign(keyword(value, { use css::text_align::*; [left, right, center, justify] })?),
+		"flex-direction" => P::FlexDirection(keyword(value, { use css::flex_direction::*; [row, row_reverse, column, column_reverse] })?),
+		"flex-wrap" => P::FlexWrap(keyword(value, { use css::flex_wrap::*; [nowrap, wrap, wrap_reverse] })?),
+		_ => return None,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_lengths() {
+		assert_eq!(parse_property("width", "120.5px"), Some(css::Property::Width(css::Dimension::Some(css::Unit::px(120.5)))));
+		assert_eq!(parse_property("max-width", "none"), Some(css::Property::MaxWidth(css::Dimension::None)));
+		assert_eq!(parse_property("top", "auto"), Some(css::Property::Top(css::PositionOffset::Auto)));
+		assert_eq!(parse_property("margin-left", "0px"), Some(css::Property::MarginLeft(css::Margin::Some(css::Unit::px(0)))));
+		assert_eq!(parse_property("padding-top", "50%"), Some(css::Property::PaddingTop(css::UnitValue::Unit(css::Unit::pct(50)))));
+		assert_eq!(parse_property("line-height", "normal"), Some(css::Property::LineHeight(css::line_height::normal)));
+		assert_eq!(parse_property("width", "calc(100% - 4px)"), None);
+	}
+
+	#[test]
+	fn parses_colors() {
+		let rgba = |r, g, b, a| Some(css::ColorValue::Rgba(css::Color { r, g, b, a }));
+		assert_eq!(color("rgb(255, 0, 10)"), rgba(255, 0, 10, 255));
+		assert_eq!(color("rgba(1, 2, 3, 0.5)"), rgba(1, 2, 3, 128));
+		assert_eq!(color("rgb(1 2 3 / 20%)"), rgba(1, 2, 3, 51));
+		assert_eq!(color("transparent"), rgba(0, 0, 0, 0));
+		assert_eq!(color("oklch(0.5 0.1 200)"), None);
+		assert_eq!(parse_property("background-color", "rgb(0, 0, 0)"), Some(css::Property::BackgroundColor(rgba(0, 0, 0, 255).unwrap())));
+	}
+
+	#[test]
+	fn parses_keywords() {
+		assert_eq!(parse_property("display", "inline-block"), Some(css::Property::Display(css::display::inline_block)));
+		assert_eq!(parse_property("position", "static"), Some(css::Property::Position(css::position::r#static)));
+		assert_eq!(parse_property("border-top-style", "solid"), Some(css::Property::BorderTopStyle(css::BorderStyle::Solid)));
+		assert_eq!(parse_property("z-index", "3"), Some(css::Property::ZIndex(css::z_index::Number(3))));
+		assert_eq!(parse_property("text-align", "start"), None);
+		assert_eq!(parse_property("transform", "matrix(1, 0, 0, 1, 0, 0)"), None);
+	}
+}
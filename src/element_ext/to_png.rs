@@ -0,0 +1,53 @@
+use hobo::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use crate::{document, window};
+
+// copy every computed property of `original` and its descendants into the inline style of their counterparts in `clone`
+fn inline_computed_styles(original: &web_sys::Element, clone: &web_sys::Element) {
+	if let Ok(Some(computed)) = window().get_computed_style(original) {
+		let css_text = (0..computed.length())
+			.map(|i| computed.item(i))
+			.map(|name| format!("{name}: {};", computed.get_property_value(&name).unwrap_or_default()))
+			.collect::<String>();
+		clone.set_attribute(web_str::style(), &css_text).ok();
+	}
+
+	let (original_children, clone_children) = (original.children(), clone.children());
+	for i in 0..original_children.length().min(clone_children.length()) {
+		let (Some(original_child), Some(clone_child)) = (original_children.item(i), clone_children.item(i)) else { continue; };
+		inline_computed_styles(&original_child, &clone_child);
+	}
+}
+
+pub(super) async fn element_to_png(element: web_sys::Element) -> Option<web_sys::Blob> {
+	let rect = element.get_bounding_client_rect();
+	let (width, height) = (rect.width(), rect.height());
+	if width <= 0. || height <= 0. { return None; }
+
+	let clone: web_sys::Element = element.clone_node_with_deep(true).ok()?.unchecked_into();
+	inline_computed_styles(&element, &clone);
+	// margins would offset the clone inside the foreignObject
+	if let Some(style) = clone.dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style) { style.set_property("margin", "0").ok(); }
+	let xhtml = web_sys::XmlSerializer::new().ok()?.serialize_to_string(&clone).ok()?;
+	let svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}"><foreignObject x="0" y="0" width="100%" height="100%">{xhtml}</foreignObject></svg>"#);
+	let url = format!("data:image/svg+xml;charset=utf-8,{}", String::from(js_sys::encode_uri_component(&svg)));
+
+	let image = web_sys::HtmlImageElement::new().ok()?;
+	image.set_src(&url);
+	if let Err(e) = JsFuture::from(image.decode()).await { log::warn!("failed to render element snapshot: {e:?}"); return None; }
+
+	let pixel_ratio = window().device_pixel_ratio();
+	let canvas: web_sys::HtmlCanvasElement = document().create_element("canvas").ok()?.unchecked_into();
+	canvas.set_width((width * pixel_ratio).ceil() as u32);
+	canvas.set_height((height * pixel_ratio).ceil() as u32);
+	let ctx: web_sys::CanvasRenderingContext2d = canvas.get_context("2d").ok()??.unchecked_into();
+	ctx.scale(pixel_ratio, pixel_ratio).ok()?;
+	ctx.draw_image_with_html_image_element_and_dw_and_dh(&image, 0., 0., width, height).ok()?;
+
+	// toBlob throws if the canvas got tainted, e.g. by cross-origin images
+	let promise = js_sys::Promise::new(&mut |resolve, reject| if let Err(e) = canvas.to_blob(&resolve) { reject.call1(&JsValue::NULL, &e).ok(); });
+	match JsFuture::from(promise).await {
+		Ok(blob) => blob.dyn_into::<web_sys::Blob>().ok(),
+		Err(e) => { log::warn!("failed to export element snapshot: {e:?}"); None },
+	}
+}
@@ -0,0 +1,24 @@
+use hobo::prelude::*;
+use super::window;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum FetchError {
+	#[error("Request failed: '{0}'.")] Network(String),
+	#[error("Request failed with status {0}.")] Status(u16),
+}
+
+/// Sends the request with the window's `fetch`.
+///
+/// Resolves with the response as long as one was received, whatever its status - use `fetch_ok` to also treat non-2xx statuses as errors.
+pub async fn fetch(request: &web_sys::Request) -> Result<web_sys::Response, FetchError> {
+	let response = wasm_bindgen_futures::JsFuture::from(window().fetch_with_request(request)).await
+		.map_err(|e| FetchError::Network(e.as_string().unwrap_or_else(|| format!("{e:?}"))))?;
+	Ok(response.unchecked_into())
+}
+
+/// Same as `fetch`, but non-2xx statuses are errors.
+pub async fn fetch_ok(request: &web_sys::Request) -> Result<web_sys::Response, FetchError> {
+	let response = fetch(request).await?;
+	if !response.ok() { return Err(FetchError::Status(response.status())); }
+	Ok(response)
+}
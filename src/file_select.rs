@@ -1,5 +1,12 @@
-use hobo::{prelude::*, create as e};
+use std::{cell::RefCell, rc::Rc};
+use hobo::{prelude::*, create as e, signal::{Mutable, SignalExt}};
 use super::document;
+use super::a11y::{announce, Politeness};
+use super::backoff::Backoff;
+use super::element_ext::{AsElementExt, children_diff::{ChildrenDiff, ChildrenDiffConfig, ChildrenDiffElementExt, ItemMapping}};
+use super::entity_ext::AsEntityExt;
+use super::fetch::FetchError;
+use super::listener::{listen, EventListener};
 #[allow(unused_imports)] use super::honk;
 
 struct FileSelect {
@@ -106,3 +113,333 @@ pub async fn open(accept: &str) -> Result<UserFile, FileError> {
 		file_load_future: None,
 	}.await
 }
+
+/// Response to an `upload`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadResponse {
+	pub status: u16,
+	pub body: String,
+}
+
+/// An in-flight `upload`, aborts the request when dropped before completing.
+pub struct Upload {
+	xhr: web_sys::XmlHttpRequest,
+	receiver: futures::channel::oneshot::Receiver<Result<UploadResponse, FetchError>>,
+	done: bool,
+	_listeners: Vec<EventListener>,
+}
+
+impl std::future::Future for Upload {
+	type Output = Result<UploadResponse, FetchError>;
+
+	fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+		let res = std::task::ready!(std::pin::Pin::new(&mut self.receiver).poll(cx));
+		self.done = true;
+		std::task::Poll::Ready(res.unwrap_or_else(|_| Err(FetchError::Network("upload canceled".to_owned()))))
+	}
+}
+
+impl Drop for Upload {
+	fn drop(&mut self) { if !self.done { self.xhr.abort().ok(); } }
+}
+
+/// POSTs `file` as the request body with `XMLHttpRequest`, since `fetch` can't report upload progress.
+///
+/// `on_progress` gets called with the uploaded fraction in 0.0..=1.0.
+/// Like `fetch`, resolves with the response whatever its status.
+pub fn upload(file: &web_sys::File, url: &str, on_progress: impl FnMut(f64) + 'static) -> Upload {
+	upload_blob(file, url, None, on_progress)
+}
+
+fn upload_blob(blob: &web_sys::Blob, url: &str, content_range: Option<&str>, mut on_progress: impl FnMut(f64) + 'static) -> Upload {
+	let (sender, receiver) = futures::channel::oneshot::channel();
+	let xhr = web_sys::XmlHttpRequest::new().unwrap();
+	let mut upload = Upload { xhr: xhr.clone(), receiver, done: false, _listeners: Vec::new() };
+
+	if let Err(e) = xhr.open("POST", url) {
+		sender.send(Err(FetchError::Network(format!("{e:?}")))).ok();
+		return upload;
+	}
+
+	if let Some(content_range) = content_range && let Err(e) = xhr.set_request_header("Content-Range", content_range) {
+		sender.send(Err(FetchError::Network(format!("{e:?}")))).ok();
+		return upload;
+	}
+
+	let sender = Rc::new(RefCell::new(Some(sender)));
+	let finish = move |res: Result<UploadResponse, FetchError>| {
+		if let Some(sender) = sender.borrow_mut().take() { sender.send(res).ok(); }
+	};
+
+	let upload_target = xhr.upload().unwrap();
+	upload._listeners.push(listen(&upload_target, "progress", move |e: web_sys::ProgressEvent| {
+		if e.length_computable() && e.total() > 0. { on_progress((e.loaded() / e.total()).clamp(0., 1.)); }
+	}));
+	upload._listeners.push(listen(&xhr, "load", #[clown::clown] move |_: web_sys::ProgressEvent| {
+		let xhr = &honk!(xhr);
+		honk!(finish)(Ok(UploadResponse {
+			status: xhr.status().unwrap_or_default(),
+			body: xhr.response_text().ok().flatten().unwrap_or_default(),
+		}));
+	}));
+	for name in ["error", "timeout", "abort"] {
+		upload._listeners.push(listen(&xhr, name, #[clown::clown] move |_: web_sys::ProgressEvent| {
+			honk!(finish)(Err(FetchError::Network(format!("upload failed: {name}"))));
+		}));
+	}
+
+	if let Err(e) = xhr.send_with_opt_blob(Some(blob)) {
+		finish(Err(FetchError::Network(format!("{e:?}"))));
+	}
+	upload
+}
+
+/// How many times a chunk of `upload_chunked` is retried before giving up.
+pub const CHUNK_RETRIES: u32 = 5;
+
+/// An in-flight `upload_chunked`, aborts the current chunk when dropped.
+pub struct ChunkedUpload {
+	next_chunk: Mutable<usize>,
+	future: std::pin::Pin<Box<dyn std::future::Future<Output = Result<UploadResponse, FetchError>>>>,
+}
+
+impl ChunkedUpload {
+	/// Index of the first chunk that hasn't been uploaded yet - pass it to `upload_chunked_from` to resume after a failure.
+	pub fn next_chunk(&self) -> usize { self.next_chunk.get() }
+	pub fn next_chunk_signal(&self) -> impl hobo::signal::Signal<Item = usize> + 'static { self.next_chunk.signal() }
+}
+
+impl std::future::Future for ChunkedUpload {
+	type Output = Result<UploadResponse, FetchError>;
+
+	fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+		self.future.as_mut().poll(cx)
+	}
+}
+
+/// Uploads `file` in `chunk_size` byte slices, one `upload` at a time, each with a `Content-Range: bytes start-end/total` header.
+///
+/// A failed chunk (network error or non-2xx status) is retried up to `CHUNK_RETRIES` times with a backoff,
+/// after that the upload resolves with the error and `next_chunk` tells where to resume from.
+/// `on_progress` reports the fraction of the whole file, resolves with the response to the last chunk.
+pub fn upload_chunked(file: &web_sys::File, url: &str, chunk_size: usize, on_progress: impl FnMut(f64) + 'static) -> ChunkedUpload {
+	upload_chunked_from(file, url, chunk_size, 0, on_progress)
+}
+
+/// Same as `upload_chunked`, but skips the chunks before `start_chunk`.
+pub fn upload_chunked_from(file: &web_sys::File, url: &str, chunk_size: usize, start_chunk: usize, on_progress: impl FnMut(f64) + 'static) -> ChunkedUpload {
+	let next_chunk = Mutable::new(start_chunk);
+	let on_progress = Rc::new(RefCell::new(on_progress));
+	let file = file.clone();
+	let url = url.to_owned();
+	let chunk_size = chunk_size.max(1) as f64;
+	let total = file.size();
+	let chunk_count = usize::max(1, (total / chunk_size).ceil() as usize);
+
+	let future = Box::pin({ let next_chunk = next_chunk.clone(); async move {
+		let mut backoff = Backoff::new(std::time::Duration::from_secs(1), std::time::Duration::from_secs(30));
+		let mut retries = 0;
+		loop {
+			let chunk = next_chunk.get();
+			if chunk >= chunk_count { return Err(FetchError::Network("nothing left to upload".to_owned())); }
+			let start = chunk as f64 * chunk_size;
+			let end = f64::min(start + chunk_size, total);
+			let slice = file.slice_with_f64_and_f64(start, end).map_err(|e| FetchError::Network(format!("{e:?}")))?;
+			let content_range = format!("bytes {}-{}/{}", start as u64, (end as u64).saturating_sub(1), total as u64);
+
+			let on_chunk_progress = #[clown::clown] move |x: f64| if total > 0. {
+				(*honk!(on_progress)).borrow_mut()((start + x * (end - start)) / total);
+			};
+			let res = upload_blob(&slice, &url, Some(&content_range), on_chunk_progress).await
+				.and_then(|x| if (200..300).contains(&x.status) { Ok(x) } else { Err(FetchError::Status(x.status)) });
+
+			match res {
+				Ok(response) => {
+					retries = 0;
+					backoff.reset();
+					next_chunk.set(chunk + 1);
+					if chunk + 1 == chunk_count {
+						(*on_progress).borrow_mut()(1.);
+						return Ok(response);
+					}
+				},
+				Err(e) if retries >= CHUNK_RETRIES => return Err(e),
+				Err(e) => {
+					retries += 1;
+					log::warn!("chunk {chunk} failed, retrying: {e}");
+					backoff.wait().await;
+				},
+			}
+		}
+	} });
+
+	ChunkedUpload { next_chunk, future }
+}
+
+// drag data type for reordering within a `FileList`, the payload is the dragged index
+const FILE_INDEX_MIME: &str = "application/x-hobo-plus-file-index";
+
+/// How long it takes `FileList::sortable` items to slide into their new place.
+pub const SORTABLE_MOVE_MS: f64 = 200.;
+
+// keys for `files` that stick with the same file across reorders, reusing the ones in `known` and making up new ones for files that aren't in there
+fn file_keys<T: PartialEq>(known: &[(T, u64)], files: &[T], next_key: &mut u64) -> Vec<u64> {
+	let mut used = vec![false; known.len()];
+	files.iter().map(|file| match known.iter().zip(&mut used).find(|((x, _), used)| !**used && x == file) {
+		Some(((_, key), used)) => { *used = true; *key },
+		None => { *next_key += 1; *next_key },
+	}).collect()
+}
+
+/// Selected files kept in a user-controlled order, e.g. for reordering before uploading.
+#[derive(Clone, Debug, Default)]
+pub struct FileList(Mutable<Vec<web_sys::File>>);
+
+impl From<Vec<web_sys::File>> for FileList {
+	fn from(files: Vec<web_sys::File>) -> Self { Self(Mutable::new(files)) }
+}
+
+impl From<&web_sys::FileList> for FileList {
+	fn from(files: &web_sys::FileList) -> Self { (0..files.length()).filter_map(|i| files.item(i)).collect::<Vec<_>>().into() }
+}
+
+impl FileList {
+	pub fn files(&self) -> Vec<web_sys::File> { self.0.get_cloned() }
+	/// Emits the files in their current order whenever they get reordered or removed.
+	pub fn files_signal(&self) -> impl hobo::signal::Signal<Item = Vec<web_sys::File>> + 'static { self.0.signal_cloned() }
+	pub fn len(&self) -> usize { self.0.lock_ref().len() }
+	pub fn is_empty(&self) -> bool { self.0.lock_ref().is_empty() }
+
+	pub fn remove(&self, index: usize) -> Option<web_sys::File> {
+		let mut files = self.0.lock_mut();
+		(index < files.len()).then(|| files.remove(index))
+	}
+
+	/// Moves the file at `from` so it ends up at `to`, shifting the ones in between.
+	pub fn move_file(&self, from: usize, to: usize) {
+		let mut files = self.0.lock_mut();
+		if from >= files.len() || to >= files.len() || from == to { return; }
+		let file = files.remove(from);
+		files.insert(to, file);
+	}
+
+	/// A list of `item` views that can be reordered by dragging them onto each other.
+	///
+	/// Items are keyed by their file, so moving one keeps its view along with whatever state it holds and slides it into place over `SORTABLE_MOVE_MS`.
+	/// That's also why `item` doesn't get an index, which would go stale on the first move.
+	/// Items can also be reordered with the keyboard - Space grabs the focused item, ArrowUp/ArrowDown move it,
+	/// Space drops it and Escape puts it back where it was, each step announced to screen readers.
+	pub fn sortable<E: AsElement + 'static>(&self, item: impl Fn(&web_sys::File) -> E + 'static) -> e::Div {
+		let list = e::div();
+		// the files in their current order along with the keys of their items
+		let keyed = Rc::new(RefCell::new(Vec::<(web_sys::File, u64)>::new()));
+		// the grabbed item's key and where it started
+		let grabbed = Mutable::new(None::<(u64, usize)>);
+
+		let insert = {
+			let (this, keyed) = (self.clone(), Rc::clone(&keyed));
+			move |&key: &u64, position: ItemMapping<u64, usize>| {
+				// only if the file got removed again before its item was inserted, which the next diff takes care of
+				let Some(file) = keyed.borrow().iter().find(|(_, x)| *x == key).map(|(file, _)| file.clone()) else { return e::div(); };
+				let (this, grabbed) = (this.clone(), grabbed.clone());
+				e::div()
+					.child(item(&file))
+					.attr(web_str::tabindex(), "0")
+					.attr("aria-roledescription", "sortable item")
+					.attr_signal("aria-grabbed", grabbed.signal().map(move |x| if x.is_some_and(|(grabbed, _)| grabbed == key) { "true" } else { "false" }))
+					.as_drag_source({ let position = position.clone(); move || (FILE_INDEX_MIME.to_owned(), position.current().to_string()) })
+					.as_drop_target(FILE_INDEX_MIME, { let (this, position) = (this.clone(), position.clone()); move |from| if let Ok(from) = from.parse() { this.move_file(from, position.current()); } })
+					.on_key_down(move |e| {
+						// keys pressed in whatever `item` rendered inside are left to it
+						if e.target() != e.current_target() { return; }
+						let (i, count) = (position.current(), this.len());
+						let position = |x: usize| format!("position {} of {count}", x + 1);
+						match (e.key().as_str(), grabbed.get()) {
+							(" ", None) => {
+								grabbed.set(Some((key, i)));
+								announce(&format!("Grabbed item at {}. Use the arrow keys to move it, Space to drop it, Escape to cancel.", position(i)), Politeness::Assertive);
+							},
+							(" ", Some(_)) => {
+								grabbed.set(None);
+								announce(&format!("Dropped at {}.", position(i)), Politeness::Assertive);
+							},
+							("Escape", Some((_, start))) => {
+								grabbed.set(None);
+								this.move_file(i, start);
+								announce(&format!("Reordering canceled, item returned to {}.", position(start)), Politeness::Assertive);
+							},
+							("ArrowUp" | "ArrowDown", Some(_)) => {
+								let to = if e.key() == "ArrowUp" { i.saturating_sub(1) } else { usize::min(i + 1, count.saturating_sub(1)) };
+								if to != i {
+									this.move_file(i, to);
+									announce(&format!("Moved to {}.", position(to)), Politeness::Assertive);
+								}
+							},
+							_ => return,
+						}
+						e.prevent_default();
+					})
+			}
+		};
+
+		// `children_diff` appends new items and leaves the rest where they are, so they're put in order once all the diffs are through
+		let reorder = move || {
+			let container = list.get_cmp::<web_sys::Element>().clone();
+			let mut ordered = {
+				let children_diff = list.get_cmp::<ChildrenDiff<u64, usize>>();
+				let positions = children_diff.mutable.lock_ref();
+				children_diff.items.iter()
+					.filter_map(|(key, element)| Some((*positions.get(key)?, element.get_cmp::<web_sys::Element>().clone())))
+					.collect::<Vec<_>>()
+			};
+			ordered.sort_by_key(|&(position, _)| position);
+
+			// moving a node blurs it, e.g. the item that's being moved with the keyboard
+			let focused = document().active_element().and_then(|x| x.dyn_into::<web_sys::HtmlElement>().ok());
+			let children = container.children();
+			for (i, (_, element)) in ordered.iter().enumerate() {
+				let current = children.item(i as u32);
+				if current.as_ref() != Some(element) { container.insert_before(element, current.as_ref().map(AsRef::as_ref)).ok(); }
+			}
+			if let Some(focused) = focused.filter(|x| document().active_element().as_ref() != Some(x.as_ref())) { focused.focus().ok(); }
+		};
+
+		let mut next_key = 0;
+		list
+			.children_diff(ChildrenDiffConfig::builder().insert(insert).on_change(reorder).animate_moves(SORTABLE_MOVE_MS))
+			.bundle(self.files_signal().subscribe(move |files| {
+				let keys = file_keys(&keyed.borrow(), &files, &mut next_key);
+				let removed = keyed.borrow().iter().map(|&(_, key)| key).filter(|key| !keys.contains(key)).collect::<Vec<_>>();
+				*keyed.borrow_mut() = files.into_iter().zip(keys.iter().copied()).collect();
+
+				let mut children_diff = list.get_cmp_mut::<ChildrenDiff<u64, usize>>();
+				for key in removed { children_diff.remove(key); }
+				for (position, key) in keys.into_iter().enumerate() {
+					let unchanged = children_diff.mutable.lock_ref().get(&key) == Some(&position);
+					if !unchanged { children_diff.upsert(key, position); }
+				}
+			}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn file_keys_follow_their_files() {
+		let mut next_key = 0;
+		let keys = file_keys(&[], &["a", "b", "c"], &mut next_key);
+		assert_eq!(keys, [1, 2, 3]);
+		let known = ["a", "b", "c"].into_iter().zip(keys).collect::<Vec<_>>();
+		assert_eq!(file_keys(&known, &["c", "a", "b"], &mut next_key), [3, 1, 2]);
+		assert_eq!(file_keys(&known, &["b", "d"], &mut next_key), [2, 4]);
+	}
+
+	#[test]
+	fn the_same_file_twice_gets_two_keys() {
+		let mut next_key = 0;
+		let known = [("a", 1), ("a", 2)];
+		assert_eq!(file_keys(&known, &["a", "a", "a"], &mut next_key), [1, 2, 1]);
+	}
+}
@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+use hobo::prelude::*;
+use super::document;
+use super::listener::{listen, listen_capture, EventListener};
+
+/// Elements that can be focusable, further narrowed down by `tabbable_elements`.
+pub const TABBABLE_SELECTOR: &str = concat!(
+	"a[href], area[href], button, input, select, textarea, iframe, summary, audio[controls], video[controls], ",
+	"[contenteditable]:not([contenteditable=\"false\"]), [tabindex]",
+);
+
+fn is_tabbable(element: &web_sys::HtmlElement) -> bool {
+	element.tab_index() >= 0
+		&& !element.has_attribute("disabled")
+		&& !element.hidden()
+		&& element.closest("[inert]").ok().flatten().is_none()
+		// not rendered, e.g. display: none on it or an ancestor
+		&& element.get_client_rects().length() > 0
+}
+
+/// Descendants of `root` reachable with Tab, in document order.
+pub fn tabbable_elements(root: &web_sys::Element) -> Vec<web_sys::HtmlElement> {
+	let Ok(candidates) = root.query_selector_all(TABBABLE_SELECTOR) else { return Vec::new(); };
+	(0..candidates.length())
+		.filter_map(|i| candidates.item(i)?.dyn_into::<web_sys::HtmlElement>().ok())
+		.filter(is_tabbable)
+		.collect()
+}
+
+/// Remembers which element had focus and focuses it again when dropped, e.g. for the lifetime of a dialog.
+pub struct FocusRestoreGuard(Option<web_sys::HtmlElement>);
+
+impl FocusRestoreGuard {
+	pub fn capture() -> Self {
+		Self(document().active_element().and_then(|x| x.dyn_into::<web_sys::HtmlElement>().ok()))
+	}
+}
+
+impl Drop for FocusRestoreGuard {
+	fn drop(&mut self) {
+		let Some(element) = self.0.take() else { return; };
+		if element.is_connected() { element.focus().ok(); }
+	}
+}
+
+struct FocusTrap {
+	element: web_sys::Element,
+	on_escape: Box<dyn FnMut()>,
+	restore: FocusRestoreGuard,
+}
+
+thread_local! {
+	static FOCUS_TRAPS: RefCell<Vec<FocusTrap>> = const { RefCell::new(Vec::new()) };
+	// installed on the first push and kept around, only the topmost trap is ever acted upon
+	static FOCUS_TRAP_LISTENERS: (EventListener, EventListener) = (
+		listen_capture(&document(), "keydown", on_trap_key_down),
+		listen_capture(&document(), "focusin", on_trap_focus_in),
+	);
+}
+
+fn top_focus_trap() -> Option<web_sys::Element> { FOCUS_TRAPS.with(|x| x.borrow().last().map(|x| x.element.clone())) }
+
+fn focus_into(element: &web_sys::Element) {
+	let target = tabbable_elements(element).into_iter().next().or_else(|| element.dyn_ref::<web_sys::HtmlElement>().cloned());
+	if let Some(target) = target { target.focus().ok(); }
+}
+
+fn on_trap_key_down(e: web_sys::KeyboardEvent) {
+	match e.key().as_str() {
+		"Escape" => {
+			let Some(FocusTrap { mut on_escape, restore, .. }) = FOCUS_TRAPS.with(|x| x.borrow_mut().pop()) else { return; };
+			e.prevent_default();
+			e.stop_propagation();
+			drop(restore);
+			on_escape();
+		},
+		"Tab" => {
+			let Some(element) = top_focus_trap() else { return; };
+			let tabbable = tabbable_elements(&element);
+			let (Some(first), Some(last)) = (tabbable.first(), tabbable.last()) else { e.prevent_default(); return; };
+			let active = document().active_element();
+			let outside = !active.as_ref().is_some_and(|x| element.contains(Some(x)));
+			let target = if e.shift_key() {
+				(outside || active.as_ref() == Some(first.as_ref())).then_some(last)
+			} else {
+				(outside || active.as_ref() == Some(last.as_ref())).then_some(first)
+			};
+			let Some(target) = target else { return; };
+			e.prevent_default();
+			target.focus().ok();
+		},
+		_ => {},
+	}
+}
+
+fn on_trap_focus_in(e: web_sys::FocusEvent) {
+	let Some(element) = top_focus_trap() else { return; };
+	let inside = e.target().and_then(|x| x.dyn_into::<web_sys::Node>().ok()).is_some_and(|x| element.contains(Some(&x)));
+	if !inside { focus_into(&element); }
+}
+
+/// Keeps focus within `element` until it's popped - Tab and Shift+Tab wrap around its tabbable descendants
+/// and focus landing anywhere outside of it is pulled back in.
+///
+/// Traps stack, so a dialog opening another dialog works as expected: only the topmost trap is active
+/// and popping it restores focus to whatever was focused when it was pushed, which is typically inside the trap underneath.
+/// Escape pops the topmost trap and then calls its `on_escape`, which should close whatever it belonged to without popping again.
+/// Focus moves to the first tabbable descendant, or to `element` itself if there are none.
+pub fn push_focus_trap(element: &web_sys::Element, on_escape: impl FnMut() + 'static) {
+	FOCUS_TRAP_LISTENERS.with(|_| ());
+	if !element.has_attribute(web_str::tabindex()) { element.set_attribute(web_str::tabindex(), "-1").ok(); }
+	let restore = FocusRestoreGuard::capture();
+	FOCUS_TRAPS.with(|x| x.borrow_mut().push(FocusTrap { element: element.clone(), on_escape: Box::new(on_escape), restore }));
+	focus_into(element);
+}
+
+/// Removes the topmost focus trap and restores focus to what was focused before it was pushed.
+pub fn pop_focus_trap() {
+	let trap = FOCUS_TRAPS.with(|x| x.borrow_mut().pop());
+	if trap.is_none() { log::warn!("no focus trap to pop"); }
+}
+
+/// Whether the topmost focus trap is the one pushed for `element`, i.e. whether `pop_focus_trap` would remove it.
+pub fn is_top_focus_trap(element: &web_sys::Element) -> bool { top_focus_trap().as_ref() == Some(element) }
+
+/// How many focus traps are currently pushed.
+pub fn focus_trap_depth() -> usize { FOCUS_TRAPS.with(|x| x.borrow().len()) }
+
+struct ActiveElement {
+	element: hobo::signal::Mutable<Option<web_sys::Element>>,
+	_listeners: [EventListener; 2],
+}
+
+thread_local! {
+	static ACTIVE_ELEMENT: ActiveElement = {
+		let document = document();
+		let body = document.body().map(web_sys::Element::from);
+		let element = hobo::signal::Mutable::new(document.active_element().filter(|x| Some(x) != body.as_ref()));
+		let focus_in = listen(&document, "focusin", #[clown::clown] move |e: web_sys::FocusEvent| {
+			honk!(element).set_neq(e.target().and_then(|x| x.dyn_into::<web_sys::Element>().ok()));
+		});
+		// `relatedTarget` is what's getting focused next, if anything - it's null when focus goes nowhere or leaves the document
+		let focus_out = listen(&document, "focusout", #[clown::clown] move |e: web_sys::FocusEvent| {
+			honk!(element).set_neq(e.related_target().and_then(|x| x.dyn_into::<web_sys::Element>().ok()));
+		});
+		ActiveElement { element, _listeners: [focus_in, focus_out] }
+	};
+}
+
+/// Signal of the currently focused element anywhere in the document, `None` when nothing is focused or focus left the document (e.g. the window got blurred).
+pub fn document_active_element_signal() -> impl hobo::signal::Signal<Item = Option<web_sys::Element>> + 'static {
+	ACTIVE_ELEMENT.with(|x| x.element.signal_cloned())
+}
@@ -1,5 +1,9 @@
 use hobo::{prelude::*, create as e};
+use super::a11y::{announce, Politeness};
+use super::element_ext::{form_control_validation_message, form_control_validity};
 use super::entity_ext::AsEntityExt;
+use super::fetch::{fetch, FetchError};
+use super::listener::listen;
 pub use tap::prelude::*;
 
 pub trait AExt: AsElement + Copy {
@@ -9,6 +13,624 @@ pub trait AExt: AsElement + Copy {
 
 impl AExt for e::A {}
 
+const TRANSPARENT_PIXEL: &str = "data:image/gif;base64,R0lGODlhAQABAIAAAAAAAP///yH5BAEAAAAALAAAAAABAAEAAAIBRAA7";
+
+/// How an image fills its box, for `ImgExt::object_fit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ObjectFit {
+	/// Stretched to the box.
+	#[default] Fill,
+	/// Scaled to fit inside the box, letterboxed.
+	Contain,
+	/// Scaled to cover the box, cropped.
+	Cover,
+	/// `None` or `Contain`, whichever is smaller.
+	ScaleDown,
+	/// At its natural size, cropped.
+	None,
+}
+
+fn reserve_aspect<T: ImgExt + 'static>(image: T, fallback: Option<(u32, u32)>) -> T {
+	use hobo::signal::SignalExt;
+	struct ReserveAspectTag;
+	let aspect = image.natural_size_signal().map(move |x| match x.or(fallback) {
+		Some((width, height)) if width > 0 && height > 0 => vec![css::Property::Raw(format!("aspect-ratio:{width}/{height};"))],
+		_ => vec![],
+	});
+	image.class_typed_signal::<ReserveAspectTag, _, _>(aspect)
+}
+
+pub trait ImgExt: AsElement + Copy {
+	/// Makes the image cover its box (`object-fit: cover`), keeping the normalized `focal` point (0..=1 on both axes) in view as much as possible.
+	///
+	/// Where `object-fit` isn't supported, falls back to a transparent image with the actual one as a covering background.
+	#[must_use]
+	fn cover_image(self, src: &str, focal: (f64, f64)) -> Self {
+		struct CoverImageTag;
+		let (x, y) = (focal.0.clamp(0., 1.) * 100., focal.1.clamp(0., 1.) * 100.);
+
+		if web_sys::css::supports_with_value("object-fit", "cover").unwrap_or(false) {
+			self
+				.attr(web_str::src(), src)
+				.class_typed::<CoverImageTag>(css::properties![
+					css::width::pct(100),
+					css::height::pct(100),
+					css::object_fit::cover,
+					// hobo_css has no `object-position`
+					css::Property::Raw(format!("object-position:{x}% {y}%;")),
+				])
+		} else {
+			self
+				.attr(web_str::src(), TRANSPARENT_PIXEL)
+				.class_typed::<CoverImageTag>(css::properties![
+					css::width::pct(100),
+					css::height::pct(100),
+					css::background_image::url(super::escape_css_url(src)),
+					css::background_size::cover,
+					css::background_repeat::no_repeat,
+					css::background_position_x::pct(x),
+					css::background_position_y::pct(y),
+				])
+		}
+	}
+
+	#[must_use]
+	fn object_fit(self, fit: ObjectFit) -> Self {
+		struct ObjectFitTag;
+		let value = match fit {
+			ObjectFit::Fill => css::object_fit::fill,
+			ObjectFit::Contain => css::object_fit::contain,
+			ObjectFit::Cover => css::object_fit::cover,
+			ObjectFit::ScaleDown => css::object_fit::scale_down,
+			ObjectFit::None => css::object_fit::none,
+		};
+		self.class_typed::<ObjectFitTag>(vec![css::Property::ObjectFit(value)])
+	}
+
+	/// Where the image sits within its box when it doesn't fill it exactly, e.g. `(css::Unit::pct(50), css::Unit::pct(0))` to crop from the bottom.
+	#[must_use]
+	fn object_position(self, (x, y): (css::Unit, css::Unit)) -> Self {
+		struct ObjectPositionTag;
+		self.class_typed::<ObjectPositionTag>(vec![css::Property::Raw(format!("object-position:{x} {y};"))])
+	}
+
+	/// Signal of the image's intrinsic `(width, height)`, e.g. to reserve its aspect ratio and avoid layout shift.
+	///
+	/// `None` until it's decoded (so the size is known before the image gets painted) and after it fails to load.
+	/// Updates whenever a new `src` finishes loading.
+	fn natural_size_signal(&self) -> impl hobo::signal::Signal<Item = Option<(u32, u32)>> + 'static {
+		let image = self.get_cmp::<web_sys::HtmlImageElement>().clone();
+		let natural_size = |image: &web_sys::HtmlImageElement| (image.natural_width() > 0).then(|| (image.natural_width(), image.natural_height()));
+		let size = hobo::signal::Mutable::new(if image.complete() { natural_size(&image) } else { None });
+
+		if size.get().is_none() {
+			let size = size.clone();
+			let image = image.clone();
+			self.spawn(async move {
+				if wasm_bindgen_futures::JsFuture::from(image.decode()).await.is_ok() { size.set_neq(natural_size(&image)); }
+			});
+		}
+		let target = image.clone().unchecked_into::<web_sys::EventTarget>();
+		self.add_bundle(listen(&target, "load", #[clown::clown] move |_: web_sys::Event| honk!(size).set_neq(natural_size(&honk!(image)))));
+		self.add_bundle(listen(&target, "error", #[clown::clown] move |_: web_sys::Event| honk!(size).set_neq(None)));
+
+		let signal = size.signal();
+		self.add_bundle(size);
+		signal
+	}
+
+	/// Sets the image's `aspect-ratio` to that of its intrinsic size as soon as that's known (see `natural_size_signal`),
+	/// so its box gets the right height before the image is painted rather than jumping when it is.
+	///
+	/// Nothing is reserved before that, see `reserve_aspect_from_load_with` to reserve space right away.
+	/// Only has an effect if the height is left `auto`.
+	#[must_use]
+	fn reserve_aspect_from_load(self) -> Self where Self: 'static { reserve_aspect(self, None) }
+
+	/// `reserve_aspect_from_load` that reserves the aspect ratio of `(width, height)` right away, e.g. with dimensions from the API that served the `src`.
+	///
+	/// The intrinsic size takes over once the image loads, and the explicit one is used again if a new `src` fails to load.
+	#[must_use]
+	fn reserve_aspect_from_load_with(self, size: (u32, u32)) -> Self where Self: 'static { reserve_aspect(self, Some(size)) }
+}
+
+impl ImgExt for e::Img {}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct Submitting(bool);
+type SubmittingState = hobo::signal::Mutable<Submitting>;
+
+pub trait FormExt: AsElement + Copy + 'static {
+	/// Submits the form with `fetch` instead of navigating away.
+	///
+	/// On `submit`, the form is serialized into `FormData`, which `build_request` turns into the request to send.
+	/// Submits while a previous request is still in flight are ignored, see `submitting_signal`.
+	#[must_use]
+	fn on_submit_fetch(self, build_request: impl Fn(web_sys::FormData) -> web_sys::Request + 'static, on_response: impl FnMut(Result<web_sys::Response, FetchError>) + 'static) -> Self {
+		self.add_component(SubmittingState::new(Submitting(false)));
+		let on_response = std::rc::Rc::new(std::cell::RefCell::new(on_response));
+		self.on_submit(move |e| {
+			e.prevent_default();
+			if self.get_cmp::<SubmittingState>().get().0 { return; }
+			let form_data = match web_sys::FormData::new_with_form(&self.get_cmp::<web_sys::HtmlFormElement>()) {
+				Ok(x) => x,
+				Err(e) => { log::error!("failed to serialize form: {e:?}"); return; },
+			};
+			let request = build_request(form_data);
+			self.get_cmp::<SubmittingState>().set(Submitting(true));
+			let on_response = std::rc::Rc::clone(&on_response);
+			self.spawn(async move {
+				let res = fetch(&request).await;
+				self.get_cmp::<SubmittingState>().set(Submitting(false));
+				(std::cell::RefCell::borrow_mut(&on_response))(res);
+			});
+		})
+	}
+
+	/// Whether a request sent by `on_submit_fetch` is in flight, e.g. for disabling the submit button.
+	///
+	/// This will panic at runtime if `on_submit_fetch()` wasn't called on the form first.
+	fn submitting_signal(self) -> impl hobo::signal::Signal<Item = bool> + 'static {
+		self.get_cmp::<SubmittingState>().signal_ref(|x| x.0)
+	}
+
+	/// Focuses the form's first invalid control and scrolls it to the middle of the screen, e.g. after a submit failed validation.
+	/// Returns whether there was one.
+	///
+	/// Its validation message is announced politely, so screen reader users hear what's wrong with it.
+	fn focus_first_invalid(&self) -> bool {
+		let Ok(controls) = self.get_cmp::<web_sys::Element>().query_selector_all("input, textarea, select") else { return false; };
+		let Some(invalid) = (0..controls.length())
+			.filter_map(|i| controls.item(i)?.dyn_into::<web_sys::HtmlElement>().ok())
+			.find(|x| !form_control_validity(x))
+		else { return false; };
+
+		invalid.focus().ok();
+		let options = web_sys::ScrollIntoViewOptions::new();
+		options.set_block(web_sys::ScrollLogicalPosition::Center);
+		invalid.scroll_into_view_with_scroll_into_view_options(&options);
+		let message = form_control_validation_message(&invalid);
+		if !message.is_empty() { announce(&message, Politeness::Polite); }
+		true
+	}
+}
+
+impl FormExt for e::Form {}
+
+/// Why a `<dialog>` closed, see `DialogExt::on_close`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DialogCloseReason {
+	/// The user dismissed it with Escape (or the platform's equivalent), i.e. it got a `cancel` event first.
+	Cancelled,
+	/// `close()` or a `<form method="dialog">` submit.
+	Closed,
+}
+
+#[derive(Clone)]
+struct DialogState {
+	open: hobo::signal::Mutable<bool>,
+	cancelled: std::rc::Rc<std::cell::Cell<bool>>,
+	last_close: std::rc::Rc<std::cell::Cell<DialogCloseReason>>,
+}
+
+// the listeners here are added before any from `on_close`, so `last_close` is up to date by the time those run
+fn dialog_state(dialog: &impl AsElement) -> DialogState {
+	if let Some(state) = dialog.try_get_cmp::<DialogState>() { return state.clone(); }
+	let element = dialog.get_cmp::<web_sys::HtmlDialogElement>().clone();
+	let state = DialogState {
+		open: hobo::signal::Mutable::new(element.open()),
+		cancelled: std::rc::Rc::default(),
+		last_close: std::rc::Rc::new(std::cell::Cell::new(DialogCloseReason::Closed)),
+	};
+	dialog.add_bundle(listen(&element, "cancel", #[clown::clown] move |_: web_sys::Event| honk!(state).cancelled.set(true)));
+	dialog.add_bundle(listen(&element, "close", #[clown::clown] move |_: web_sys::Event| {
+		let state = &honk!(state);
+		let reason = if state.cancelled.replace(false) { DialogCloseReason::Cancelled } else { DialogCloseReason::Closed };
+		state.last_close.set(reason);
+		state.open.set_neq(false);
+	}));
+	dialog.add_component(state.clone());
+	state
+}
+
+/// Native `<dialog>`, which comes with focus trapping, top layer stacking and Escape handling for free -
+/// a lighter alternative to a custom modal when those are all that's needed.
+pub trait DialogExt: AsElement + Copy + 'static {
+	/// Opens the dialog as a modal, making the rest of the page inert.
+	fn show_modal(&self) {
+		let state = dialog_state(self);
+		state.cancelled.set(false);
+		if let Err(e) = self.get_cmp::<web_sys::HtmlDialogElement>().show_modal() { log::warn!("failed to open dialog: {e:?}"); return; }
+		state.open.set_neq(true);
+	}
+
+	fn close(&self) {
+		dialog_state(self).cancelled.set(false);
+		self.get_cmp::<web_sys::HtmlDialogElement>().close();
+	}
+
+	fn is_open(&self) -> bool { self.get_cmp::<web_sys::HtmlDialogElement>().open() }
+
+	/// Signal of whether the dialog is open, including closing by Escape or `<form method="dialog">`.
+	/// Opening it some other way than `show_modal` isn't noticed.
+	fn open_signal(&self) -> impl hobo::signal::Signal<Item = bool> + 'static { dialog_state(self).open.signal() }
+
+	/// Calls `f` after the dialog closes, whichever way that happened.
+	#[must_use]
+	fn on_close(self, mut f: impl FnMut(DialogCloseReason) + 'static) -> Self {
+		let state = dialog_state(&self);
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		self.bundle(listen(&target, "close", move |_: web_sys::Event| f(state.last_close.get())))
+	}
+
+	/// Calls `f` when the user tries to dismiss the dialog with Escape, before it closes - `prevent_default` on the event keeps it open.
+	#[must_use]
+	fn on_cancel(self, mut f: impl FnMut(web_sys::Event) + 'static) -> Self {
+		let state = dialog_state(&self);
+		let target = self.get_cmp::<web_sys::EventTarget>().clone();
+		self.bundle(listen(&target, "cancel", move |e: web_sys::Event| {
+			f(e.clone());
+			// stays open, so whatever closes it later isn't the cancel
+			if e.default_prevented() { state.cancelled.set(false); }
+		}))
+	}
+}
+
+impl DialogExt for e::Dialog {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum MaskSlot {
+	Digit,
+	Letter,
+	Alphanumeric,
+}
+
+impl MaskSlot {
+	fn parse(c: char) -> Option<Self> {
+		match c {
+			'#' => Some(Self::Digit),
+			'A' => Some(Self::Letter),
+			'*' => Some(Self::Alphanumeric),
+			_ => None,
+		}
+	}
+
+	fn accepts(self, c: char) -> bool {
+		match self {
+			Self::Digit => c.is_ascii_digit(),
+			Self::Letter => c.is_alphabetic(),
+			Self::Alphanumeric => c.is_alphanumeric(),
+		}
+	}
+}
+
+struct InputMask(String);
+
+// fills the pattern's slots with `raw` chars, skipping the ones a slot doesn't accept,
+// returns the formatted text, the raw chars that made it in and the char index for the caret if it was after the first `caret_raw` raw chars
+fn apply_mask(pattern: &str, raw: &[char], caret_raw: usize) -> (String, String, usize) {
+	let mut formatted = String::new();
+	let mut accepted = String::new();
+	let mut caret = (caret_raw == 0).then_some(0);
+	let mut raw = raw.iter().copied().enumerate().peekable();
+	for p in pattern.chars() {
+		let Some(slot) = MaskSlot::parse(p) else {
+			// literals only go in once there's something to follow them
+			if raw.peek().is_none() { break; }
+			formatted.push(p);
+			continue;
+		};
+		let filled = raw.by_ref().any(|(i, c)| {
+			let ok = slot.accepts(c);
+			if ok { formatted.push(c); accepted.push(c); }
+			if i + 1 == caret_raw { caret = Some(formatted.chars().count()); }
+			ok
+		});
+		if !filled { break; }
+	}
+	let caret = caret.unwrap_or_else(|| formatted.chars().count());
+	(formatted, accepted, caret)
+}
+
+fn is_mask_input(c: char) -> bool { c.is_alphanumeric() }
+
+// the input chars in `value` and how many of them come before the char index `caret`, i.e. everything but the pattern's literals,
+// which only count as literals where the pattern has them, so they can be letters or digits too - like the `1` in `"+1 (###) ###-####"`
+fn unmask(pattern: &str, value: &str, caret: usize) -> (Vec<char>, usize) {
+	let pattern = pattern.chars().collect::<Vec<_>>();
+	let value = value.chars().collect::<Vec<_>>();
+	let mut slot = 0;
+	let mut raw = Vec::new();
+	let mut caret_raw = 0;
+	for (i, &c) in value.iter().enumerate() {
+		let literals = pattern[slot..].iter().take_while(|&&x| MaskSlot::parse(x).is_none()).count();
+		// literals missing in front of `c` got deleted, and input chars only count as literals with something after them
+		// since that's when `apply_mask` puts literals in - otherwise a `1` typed into an empty `"+1 ..."` would just vanish
+		if let Some(k) = (0..literals).find(|&k| pattern[slot + k] == c && (!is_mask_input(c) || i + 1 < value.len())) {
+			slot += k + 1;
+			continue;
+		}
+		slot += literals;
+		if !is_mask_input(c) { continue; }
+		raw.push(c);
+		if i < caret { caret_raw += 1; }
+		if pattern.get(slot).and_then(|&x| MaskSlot::parse(x)).is_some_and(|x| x.accepts(c)) { slot += 1; }
+	}
+	(raw, caret_raw)
+}
+
+// what an input event leaves in a masked input with `value` and the caret at the char index `caret`,
+// `previous_raw` being the raw chars from before it - returns the same as `apply_mask`
+fn mask_input(pattern: &str, previous_raw: &str, value: &str, caret: usize, backspace: bool) -> (String, String, usize) {
+	let (mut raw, mut caret_raw) = unmask(pattern, value, caret);
+
+	// only a literal got deleted, which formatting would just put back
+	if backspace && caret_raw > 0 && raw.iter().copied().eq(previous_raw.chars()) {
+		raw.remove(caret_raw - 1);
+		caret_raw -= 1;
+	}
+
+	apply_mask(pattern, &raw, caret_raw)
+}
+
+fn utf16_to_char_index(s: &str, utf16: u32) -> usize {
+	let mut units = 0;
+	s.chars().take_while(|c| { units += c.len_utf16() as u32; units <= utf16 }).count()
+}
+
+fn char_to_utf16_index(s: &str, chars: usize) -> u32 {
+	s.chars().take(chars).map(|c| c.len_utf16() as u32).sum()
+}
+
+/// How long `InputExt::autocomplete` waits after the last keystroke before fetching suggestions.
+pub const AUTOCOMPLETE_DEBOUNCE_MS: u32 = 250;
+
+static NEXT_AUTOCOMPLETE_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+// the suggestions live in <body> rather than next to the input, so they have to be removed along with it
+struct AutocompleteDropdown(e::Div);
+
+impl Drop for AutocompleteDropdown {
+	fn drop(&mut self) { if !self.0.is_dead() { self.0.remove(); } }
+}
+
+fn suggestion_count_message(count: usize) -> String {
+	match count {
+		0 => "No suggestions".to_owned(),
+		1 => "1 suggestion".to_owned(),
+		n => format!("{n} suggestions"),
+	}
+}
+
+pub trait InputExt: AsElement + Copy + 'static {
+	/// Formats what's typed into the `pattern` as it's typed, e.g. `"(###) ###-####"` for phone numbers.
+	///
+	/// `#` is a digit, `A` is a letter and `*` is either, everything else is a literal that gets filled in automatically.
+	/// Typed or pasted characters that don't fit the next slot are dropped, as is anything past the end of the pattern.
+	/// The caret stays after the same input character, and backspacing over a literal deletes the character before it.
+	/// Literals can be letters or digits too, as in `"+1 (###) ###-####"` - they're told apart from input by where they are in the value,
+	/// so typing `1` into it while empty gives `"+1 (1"`, but pasting `"+1 555 123 4567"` gives `"+1 (555) 123-4567"`. See `raw_value` for the value without the literals.
+	#[must_use]
+	fn input_mask(self, pattern: &str) -> Self {
+		let input = self.get_cmp::<web_sys::HtmlInputElement>().clone();
+		let pattern = pattern.to_owned();
+		let (initial, _) = unmask(&pattern, &input.value(), 0);
+		let (formatted, accepted, _) = apply_mask(&pattern, &initial, 0);
+		input.set_value(&formatted);
+		let previous_raw = std::cell::RefCell::new(accepted);
+
+		let target = input.clone().unchecked_into::<web_sys::EventTarget>();
+		self
+			.bundle(listen(&target, "input", #[clown::clown] move |e: web_sys::InputEvent| {
+				let input = &honk!(input);
+				let value = input.value();
+				let caret = utf16_to_char_index(&value, input.selection_start().ok().flatten().unwrap_or(u32::MAX));
+				let backspace = e.input_type() == "deleteContentBackward";
+				let (formatted, accepted, caret) = mask_input(&honk!(pattern), &previous_raw.borrow(), &value, caret, backspace);
+				previous_raw.replace(accepted);
+				if formatted == value { return; }
+				input.set_value(&formatted);
+				let focused = super::document().active_element().is_some_and(|x| x.is_same_node(Some(input)));
+				if focused {
+					let caret = char_to_utf16_index(&formatted, caret);
+					input.set_selection_range(caret, caret).ok();
+				}
+			}))
+			.component(InputMask(pattern))
+	}
+
+	/// The value without an `input_mask`'s literals, e.g. `"5551234567"` for `"(555) 123-4567"`. Just the value if there's no mask.
+	fn raw_value(&self) -> String {
+		let value = self.get_cmp::<web_sys::HtmlInputElement>().value();
+		let Some(mask) = self.try_get_cmp::<InputMask>() else { return value; };
+		let (raw, _) = unmask(&mask.0, &value, 0);
+		apply_mask(&mask.0, &raw, 0).1
+	}
+
+	/// Suggests completions in a dropdown while typing - a combobox with a listbox popup in ARIA terms.
+	///
+	/// `fetch` gets the value once typing pauses for `AUTOCOMPLETE_DEBOUNCE_MS`. A fetch still in flight is dropped as soon as the value changes again,
+	/// so stale suggestions never show up - though dropping it doesn't cancel a network request by itself, `fetch` needs an `AbortSignal` for that.
+	/// Suggestions are rendered with `render_item` as options in a listbox that's appended to `<body>` on a popover layer and kept under the input with `position::anchor_popover`.
+	/// ArrowDown/ArrowUp move through them, Enter or a click passes the chosen one to `on_select`, Escape or the input losing focus (e.g. clicking elsewhere) closes the list.
+	/// The number of suggestions is announced to screen readers whenever they arrive.
+	/// The value isn't changed on selection, that's up to `on_select`.
+	#[must_use]
+	fn autocomplete<Item, Fut, E>(self, fetch: impl Fn(String) -> Fut + 'static, render_item: impl Fn(&Item) -> E + 'static, on_select: impl FnMut(Item) + 'static) -> Self where
+		Item: 'static,
+		Fut: std::future::Future<Output = Vec<Item>> + 'static,
+		E: AsElement,
+	{
+		use std::{cell::{Cell, RefCell}, rc::Rc};
+		use hobo::signal::{Mutable, SignalExt};
+		use super::position::{anchor_popover, Alignment, PositionOptions};
+		use super::stacking::{on_layer, LayerKind};
+
+		let id = NEXT_AUTOCOMPLETE_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+		let listbox_id = format!("autocomplete-{id}-listbox");
+		let option_id = move |i: usize| format!("autocomplete-{id}-option-{i}");
+		let input = self.get_cmp::<web_sys::HtmlInputElement>().clone();
+		let items = Rc::new(RefCell::new(Vec::<Item>::new()));
+		let active = Mutable::new(None::<usize>);
+		let open = Mutable::new(false);
+
+		let list = Rc::new(Cell::new(e::div()));
+		let dropdown = on_layer(e::div(), LayerKind::Popover)
+			.attr("role", "listbox")
+			.id(&listbox_id)
+			.bool_attr_signal(web_str::hidden(), open.signal().map(|x| !x))
+			.child(list.get())
+			// keeps focus in the input while clicking suggestions or dragging the scrollbar
+			.on_mouse_down(|e| e.prevent_default())
+			.allow_no_parent();
+		match super::document().body() {
+			Some(body) => { body.append_child(&dropdown.get_cmp::<web_sys::Node>()).ok(); },
+			None => log::warn!("can't show autocomplete suggestions without a body"),
+		}
+		anchor_popover(&self, &dropdown, PositionOptions { alignment: Alignment::Start, offset: 4., ..PositionOptions::default() });
+
+		let close = Rc::new(#[clown::clown] move || {
+			honk!(open).set_neq(false);
+			honk!(active).set_neq(None);
+		});
+		let on_select = RefCell::new(on_select);
+		let select = Rc::new(#[clown::clown] move |i: usize| {
+			let Some(item) = std::mem::take(&mut *honk!(items).borrow_mut()).into_iter().nth(i) else { return; };
+			honk!(close)();
+			(on_select.borrow_mut())(item);
+		});
+		let show = {
+			let (items, active, open, select) = (Rc::clone(&items), active.clone(), open.clone(), Rc::clone(&select));
+			Rc::new(move |results: Vec<Item>| {
+				let options = results.iter().enumerate().map(|(i, item)| {
+					let select = Rc::clone(&select);
+					e::div()
+						.attr("role", "option")
+						.id(option_id(i))
+						.attr_signal("aria-selected", active.signal().map(move |x| if x == Some(i) { "true" } else { "false" }))
+						.child(render_item(item))
+						.on_click(move |_| select(i))
+				}).collect::<Vec<_>>();
+				list.set(list.get().replace_with(e::div().children(options)));
+				let count = results.len();
+				*items.borrow_mut() = results;
+				active.set_neq(None);
+				open.set_neq(count > 0);
+				announce(&suggestion_count_message(count), Politeness::Polite);
+			})
+		};
+
+		let fetch = Rc::new(fetch);
+		let pending = RefCell::new(None);
+		let on_input = #[clown::clown] move |_| {
+			// dropping the handle cancels the previous debounce or fetch
+			pending.replace(None);
+			honk!(active).set_neq(None);
+			let value = honk!(input).value();
+			if value.trim().is_empty() { honk!(items).borrow_mut().clear(); honk!(close)(); return; }
+			let (fetch, show) = (Rc::clone(&fetch), Rc::clone(&show));
+			let (handle, fut) = hobo::futures_signals::cancelable_future(async move {
+				async_timer::new_timer(std::time::Duration::from_millis(AUTOCOMPLETE_DEBOUNCE_MS.into())).await;
+				show(fetch(value).await);
+			}, Default::default);
+			wasm_bindgen_futures::spawn_local(fut);
+			pending.replace(Some(handle));
+		};
+		let on_key_down = #[clown::clown] move |e: web_sys::KeyboardEvent| {
+			let (active, open) = (&honk!(active), &honk!(open));
+			let count = honk!(items).borrow().len();
+			match e.key().as_str() {
+				key @ ("ArrowDown" | "ArrowUp") if count > 0 => {
+					e.prevent_default();
+					let next = match (active.get(), key == "ArrowDown") {
+						(None, true) => 0,
+						(None, false) => count - 1,
+						(Some(i), true) => (i + 1) % count,
+						(Some(i), false) => (i + count - 1) % count,
+					};
+					open.set_neq(true);
+					active.set_neq(Some(next));
+				},
+				"Enter" => if let (true, Some(i)) = (open.get(), active.get()) {
+					e.prevent_default();
+					honk!(select)(i);
+				},
+				"Escape" if open.get() => {
+					e.prevent_default();
+					honk!(close)();
+				},
+				_ => {},
+			}
+		};
+
+		let input_element = self.get_cmp::<web_sys::Element>().clone();
+		self.add_bundle(active.signal().subscribe(move |i| match i {
+			Some(i) => {
+				input_element.set_attribute("aria-activedescendant", &option_id(i)).ok();
+				let Some(option) = super::document().get_element_by_id(&option_id(i)) else { return; };
+				let options = web_sys::ScrollIntoViewOptions::new();
+				options.set_block(web_sys::ScrollLogicalPosition::Nearest);
+				option.scroll_into_view_with_scroll_into_view_options(&options);
+			},
+			None => { input_element.remove_attribute("aria-activedescendant").ok(); },
+		}));
+
+		self
+			.attr("role", "combobox")
+			.attr("aria-autocomplete", "list")
+			.attr("aria-controls", &listbox_id)
+			.attr_signal("aria-expanded", open.signal().map(|x| if x { "true" } else { "false" }))
+			.attr(web_str::autocomplete(), "off")
+			.on_input(on_input)
+			.on_key_down(on_key_down)
+			.on_blur(move |_| close())
+			.component(AutocompleteDropdown(dropdown))
+	}
+}
+
+impl InputExt for e::Input {}
+
+pub trait StringValueExt: StringValue + Copy + 'static {
+	/// Two-way binds the value to a field of a shared `model`, without needing a `Mutable` per field.
+	///
+	/// `get` reads the field to display whenever the model changes, `set` writes the value back into the model in place on every `input`.
+	/// The value is only overwritten when it actually differs, so the caret doesn't jump around while typing.
+	#[must_use]
+	fn bind_field<M: 'static>(self, model: hobo::signal::Mutable<M>, get: impl Fn(&M) -> String + 'static, set: impl Fn(&mut M, String) + 'static) -> Self {
+		self.add_bundle(model.signal_ref(get).subscribe(move |x| if self.value() != x { self.set_value(&x); }));
+		self.on_input(move |_| set(&mut model.lock_mut(), self.value()))
+	}
+
+	/// Reports the value on every `input`, for when it isn't worth owning the value in a `Mutable`.
+	#[must_use]
+	fn on_value_change(self, mut f: impl FnMut(String) + 'static) -> Self {
+		self.on_input(move |_| f(self.value()))
+	}
+
+	/// Reports the value once editing is done - on `change` or when losing focus,
+	/// but only if it differs from what it was when the element got focused or last reported.
+	#[must_use]
+	fn on_value_commit(self, f: impl FnMut(String) + 'static) -> Self {
+		let committed = std::rc::Rc::new(std::cell::RefCell::new(self.value()));
+		let f = std::rc::Rc::new(std::cell::RefCell::new(f));
+		let commit = {
+			let committed = std::rc::Rc::clone(&committed);
+			move || {
+				let value = self.value();
+				if *committed.borrow() == value { return; }
+				committed.replace(value.clone());
+				(std::cell::RefCell::borrow_mut(&f))(value);
+			}
+		};
+		self
+			.on_focus(move |_| { committed.replace(self.value()); })
+			.on_change({ let commit = commit.clone(); move |_| commit() })
+			.on_blur(move |_| commit())
+	}
+}
+
+impl<T: StringValue + Copy + 'static> StringValueExt for T {}
+
 // Don't make this public, make a Toggleable and use it's value_signals/set_value
 /// Generic `bool` component for checbox/switch like events.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
@@ -63,3 +685,82 @@ impl Toggleable {
 	// Bypasses the 'static requirement which is necessary when using Toggleable directly.
 	#[must_use] pub fn toggle_on_click(self) -> Self where Self: AsElement + Copy { self.on_click(move |_| self.toggle()) }
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PHONE: &str = "(###) ###-####";
+
+	// types `typed` one char at a time at the end, like a user would
+	fn type_into(pattern: &str, typed: &str) -> (String, String) {
+		let (mut value, mut raw) = (String::new(), String::new());
+		for c in typed.chars() {
+			value.push(c);
+			let (formatted, accepted, caret) = mask_input(pattern, &raw, &value, value.chars().count(), false);
+			assert_eq!(caret, formatted.chars().count());
+			(value, raw) = (formatted, accepted);
+		}
+		(value, raw)
+	}
+
+	#[test]
+	fn formats_typing() {
+		assert_eq!(type_into(PHONE, "555"), ("(555".to_owned(), "555".to_owned()));
+		assert_eq!(type_into(PHONE, "5551234"), ("(555) 123-4".to_owned(), "5551234".to_owned()));
+		assert_eq!(type_into(PHONE, "555123456789"), ("(555) 123-4567".to_owned(), "5551234567".to_owned()));
+		assert_eq!(type_into(PHONE, "5a5-5"), ("(555".to_owned(), "555".to_owned()));
+		assert_eq!(type_into("AA-##", "ab12"), ("ab-12".to_owned(), "ab12".to_owned()));
+	}
+
+	#[test]
+	fn backspace_over_a_literal_deletes_the_char_before_it() {
+		// "(555) 123-4" with the caret after the `-`, backspaced
+		let (formatted, raw, caret) = mask_input(PHONE, "5551234", "(555) 1234", 9, true);
+		assert_eq!((formatted.as_str(), raw.as_str(), caret), ("(555) 124", "555124", 8));
+
+		// deleting a char rather than a literal leaves the rest be
+		let (formatted, raw, caret) = mask_input(PHONE, "5551234", "(555) 13-4", 7, true);
+		assert_eq!((formatted.as_str(), raw.as_str(), caret), ("(555) 134", "555134", 7));
+	}
+
+	#[test]
+	fn pasting_drops_rejected_chars() {
+		let (formatted, raw, caret) = mask_input(PHONE, "", "555.123.4567", 12, false);
+		assert_eq!((formatted.as_str(), raw.as_str(), caret), ("(555) 123-4567", "5551234567", 14));
+
+		let (formatted, raw, _) = mask_input("##-##", "", "1a2b3c4d5", 9, false);
+		assert_eq!((formatted.as_str(), raw.as_str()), ("12-34", "1234"));
+
+		// pasted in the middle, the caret ends up after the last pasted char
+		let (formatted, raw, caret) = mask_input(PHONE, "5554567", "(555) x12y3-4567", 11, false);
+		assert_eq!((formatted.as_str(), raw.as_str(), caret), ("(555) 123-4567", "5551234567", 9));
+	}
+
+	#[test]
+	fn literals_can_be_digits() {
+		const INTERNATIONAL: &str = "+1 (###) ###-####";
+		assert_eq!(type_into(INTERNATIONAL, "1555"), ("+1 (155) 5".to_owned(), "1555".to_owned()));
+		assert_eq!(type_into(INTERNATIONAL, "555"), ("+1 (555".to_owned(), "555".to_owned()));
+		assert_eq!(unmask(INTERNATIONAL, "+1 (555) 123-4567", 0).0.into_iter().collect::<String>(), "5551234567");
+		let (formatted, raw, _) = mask_input(INTERNATIONAL, "", "+1 555 123 4567", 15, false);
+		assert_eq!((formatted.as_str(), raw.as_str()), ("+1 (555) 123-4567", "5551234567"));
+		// the deleted ` ` of `+1 (` is put back rather than the `1` turning into input
+		let (formatted, raw, _) = mask_input(INTERNATIONAL, "555", "+1(555", 2, true);
+		assert_eq!((formatted.as_str(), raw.as_str()), ("+1 (555", "555"));
+	}
+
+	#[test]
+	fn maps_caret_indices() {
+		let s = "a😀b";
+		assert_eq!(utf16_to_char_index(s, 0), 0);
+		assert_eq!(utf16_to_char_index(s, 1), 1);
+		assert_eq!(utf16_to_char_index(s, 3), 2);
+		assert_eq!(utf16_to_char_index(s, 4), 3);
+		assert_eq!(utf16_to_char_index(s, u32::MAX), 3);
+		assert_eq!(char_to_utf16_index(s, 0), 0);
+		assert_eq!(char_to_utf16_index(s, 2), 3);
+		assert_eq!(char_to_utf16_index(s, 3), 4);
+		for chars in 0..=3 { assert_eq!(utf16_to_char_index(s, char_to_utf16_index(s, chars)), chars); }
+	}
+}
@@ -0,0 +1,30 @@
+use hobo::prelude::*;
+use super::{window, listener::{listen, EventListener}};
+
+fn navigator_languages() -> Vec<String> {
+	window().navigator().languages().iter().filter_map(|x| x.as_string()).collect()
+}
+
+thread_local! {
+	static LANGUAGES: (hobo::signal::Mutable<Vec<String>>, EventListener) = {
+		let languages = hobo::signal::Mutable::new(navigator_languages());
+		let listener = listen(&window(), "languagechange", { let languages = languages.clone(); move |_: web_sys::Event| languages.set(navigator_languages()) });
+		(languages, listener)
+	};
+}
+
+/// The user's preferred languages (`navigator.languages`), most preferred first.
+///
+/// Updates on `languagechange`, e.g. when the user changes their system language.
+pub fn languages_signal() -> impl hobo::signal::Signal<Item = Vec<String>> + 'static {
+	LANGUAGES.with(|(languages, _)| languages.signal_cloned())
+}
+
+pub fn languages() -> Vec<String> {
+	LANGUAGES.with(|(languages, _)| languages.get_cloned())
+}
+
+/// The locale formatting should default to when none is specified - the most preferred language, if there is any.
+pub fn default_locale() -> Option<String> {
+	LANGUAGES.with(|(languages, _)| languages.lock_ref().first().cloned())
+}
@@ -2,59 +2,169 @@
 
 use hobo::prelude::*;
 #[allow(unused_imports)] use clown::{clown, honk, slip};
+pub use context::Context;
 pub use entity_ext::AsEntityExt;
-pub use element_ext::{children_diff::{ChildrenDiff, ChildrenDiffConfig, ChildrenDiffConfigBuilder, ChildrenDiffElementExt, ItemMapping}, AsElementExt, FontTag, Clicked};
-pub use html_ext::{AExt, Toggleable, ToggleableExt};
+pub use element_ext::{children_diff::{ChildrenDiff, ChildrenDiffConfig, ChildrenDiffConfigBuilder, ChildrenDiffElementExt, ItemMapping}, AsElementExt, AnimEventKind, CONFIRM_ACTION_TIMEOUT_MS, FontTag, Clicked, DismissReason, EdgeScrollConfig, Edges, FLASH_REDUCED_MOTION_MS, LengthUnit, SIZE_EPSILON_PX, INTERSECTION_RATIO_STEP, LAZY_BACKGROUND_MARGIN, StyleSnapshot, SWIPE_DISMISS_FRACTION, SWIPE_FLING_VELOCITY, ZoomInfo, RevealConfig, RevealKind, UserSelect};
+pub use observer::ObserverHandle;
+pub use html_ext::{AExt, AUTOCOMPLETE_DEBOUNCE_MS, DialogCloseReason, DialogExt, FormExt, ImgExt, InputExt, ObjectFit, StringValueExt, Toggleable, ToggleableExt};
+pub use spring::{spring, SpringConfig, SpringValue};
 pub use svg::xml_to_svg;
 pub use __svgs as svgs;
 
 mod html_ext;
 mod entity_ext;
 mod element_ext;
+pub mod listener;
+mod observer;
+pub mod a11y;
+pub mod backoff;
+pub mod clipboard;
+pub mod components;
+pub mod context;
+pub mod effects;
+pub mod fetch;
 pub mod file_select;
+pub mod focus;
+pub mod intl;
+pub mod position;
 pub mod svg;
+pub mod scheduler;
+pub mod shortcuts;
 pub mod socket;
+pub mod spring;
+pub mod stacking;
+pub mod tabs;
+pub mod text;
+pub mod units;
 
 pub fn window() -> web_sys::Window { web_sys::window().expect("no window") }
 pub fn document() -> web_sys::Document { window().document().expect("no document") }
 
+/// Whether the user asked for reduced motion (`prefers-reduced-motion: reduce`), animations should be skipped or toned down if so.
+pub fn prefers_reduced_motion() -> bool {
+	window().match_media("(prefers-reduced-motion: reduce)").ok().flatten().is_some_and(|x| x.matches())
+}
+
+/// Width of the browser's scrollbars in px, 0 on platforms with overlay scrollbars.
+///
+/// Measured with an offscreen div with forced scrollbars on first call and cached afterwards.
+pub fn scrollbar_width() -> f64 {
+	thread_local! { static SCROLLBAR_WIDTH: std::cell::OnceCell<f64> = const { std::cell::OnceCell::new() }; }
+
+	SCROLLBAR_WIDTH.with(|cached| {
+		if let Some(width) = cached.get() { return *width; }
+		let document = document();
+		// don't cache anything if there's nowhere to measure yet
+		let Some(body) = document.body() else { return 0.; };
+		let div: web_sys::HtmlElement = document.create_element("div").unwrap().unchecked_into();
+		div.set_attribute(web_str::style(), "position: absolute; top: -9999px; width: 100px; height: 100px; overflow: scroll;").unwrap();
+		body.append_child(&div).unwrap();
+		let width = f64::from(div.offset_width() - div.client_width());
+		div.remove();
+		*cached.get_or_init(|| width)
+	})
+}
+
 fn closure_mut<T: wasm_bindgen::convert::FromWasmAbi + 'static> (closure: impl FnMut(T) + 'static) -> Closure<dyn FnMut(T)> {
 	Closure::wrap(Box::new(closure) as Box<dyn FnMut(T) + 'static>)
 }
 
-pub fn animation(f: impl FnMut(f64) -> bool + 'static) {
-	animation_with_window(&window(), f);
+// runs `f` on the next animation frame, unless something scheduled with the same `pending` flag hasn't ran yet
+fn request_frame_coalesced(pending: &std::rc::Rc<std::cell::Cell<bool>>, f: impl FnOnce() + 'static) {
+	if pending.replace(true) { return; }
+	let pending = std::rc::Rc::clone(pending);
+	window().request_animation_frame(Closure::once_into_js(move || { pending.set(false); f(); }).unchecked_ref()).unwrap();
+}
+
+// `url` escaped to go between the quotes of a css `url("...")`, so that it can't end the string and inject rules of its own
+fn escape_css_url(url: &str) -> String {
+	url.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\a ")
+}
+
+type AnimationCallback = std::rc::Rc<std::cell::RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+/// A running `animation`, it goes on until its closure returns `false` or it gets cancelled.
+///
+/// Dropping the handle doesn't stop the animation, so existing fire-and-forget callers are unaffected.
+#[derive(Clone)]
+pub struct AnimationHandle {
+	window: web_sys::Window,
+	callback: AnimationCallback,
+	frame: std::rc::Rc<std::cell::Cell<Option<i32>>>,
 }
 
-// run a function every frame until it returns false
+impl AnimationHandle {
+	/// Stops the animation, its closure won't be called again. Does nothing if it already stopped.
+	///
+	/// Safe to call mid-frame, even from within the closure itself - the stored closure is only borrowed while scheduling the next frame,
+	/// and that borrow is released before the closure runs.
+	pub fn cancel(&self) {
+		if let Some(frame) = self.frame.take() { self.window.cancel_animation_frame(frame).ok(); }
+		let _drop = self.callback.borrow_mut().take();
+	}
+
+	pub fn is_running(&self) -> bool { self.callback.borrow().is_some() }
+}
+
+pub fn animation(f: impl FnMut(f64) -> bool + 'static) -> AnimationHandle {
+	animation_with_window(&window(), f)
+}
+
+/// Fixed-duration tween - runs `f` every frame with the progress from `0.` to `1.` until `duration_ms` have passed.
+///
+/// The last call is always exactly `1.`, even when the final frame overshoots the duration.
+pub fn animation_for_duration(duration_ms: f64, f: impl FnMut(f64) + 'static) -> AnimationHandle {
+	animation_for_duration_with_window(&window(), duration_ms, f)
+}
+
+pub fn animation_for_duration_with_window(window: &web_sys::Window, duration_ms: f64, mut f: impl FnMut(f64) + 'static) -> AnimationHandle {
+	let mut elapsed = 0.;
+	animation_with_window(window, move |delta_t| {
+		elapsed += delta_t;
+		let progress = if duration_ms > 0. { f64::min(elapsed / duration_ms, 1.) } else { 1. };
+		f(progress);
+		progress < 1.
+	})
+}
+
+// run a function every frame until it returns false or the returned handle is cancelled
 // fn argument is delta milliseconds
 // skips the first frame immediately after because it's not possible to calculate time delta
 #[expect(clippy::clone_on_ref_ptr)]
-pub fn animation_with_window(window: &web_sys::Window, mut f: impl FnMut(f64) -> bool + 'static) {
-	use std::{cell::RefCell, rc::Rc};
+pub fn animation_with_window(window: &web_sys::Window, mut f: impl FnMut(f64) -> bool + 'static) -> AnimationHandle {
+	use std::{cell::{Cell, RefCell}, rc::Rc};
 
 	// this weird refcelling is necessary for "recursion"
-	let cb = Rc::new(RefCell::new(None as Option<Closure<dyn FnMut(f64) + 'static>>));
+	let cb: AnimationCallback = Rc::new(RefCell::new(None));
+	// the pending request, so cancelling can call it off
+	let frame = Rc::new(Cell::new(None));
 	let mut last_timestamp = None;
 	*cb.borrow_mut() = Some(Closure::wrap(Box::new(#[clown] |timestamp| {
 		let cb = Rc::clone(&honk!(cb));
+		let frame = Rc::clone(&honk!(frame));
 		let window = honk!(window).clone();
+		frame.set(None);
+		// the closure is gone if the handle got cancelled from within `f`
+		let request_next = || if let Some(cb) = cb.borrow().as_ref() {
+			frame.set(window.request_animation_frame(cb.as_ref().unchecked_ref()).ok());
+		};
 
 		if window.closed().unwrap_or(true) { let _drop = cb.borrow_mut().take(); return; }
 		let Some(last_timestamp) = last_timestamp.as_mut() else {
-			window.request_animation_frame(cb.borrow().as_ref().unwrap().as_ref().unchecked_ref()).unwrap();
+			request_next();
 			last_timestamp = Some(timestamp);
 			return;
 		};
 		let delta_t = timestamp - *last_timestamp;
 		*last_timestamp = timestamp;
 		if f(delta_t) {
-			window.request_animation_frame(cb.borrow().as_ref().unwrap().as_ref().unchecked_ref()).unwrap();
+			request_next();
 		} else {
 			let _drop = cb.borrow_mut().take();
 		}
 	}) as Box<dyn FnMut(f64) + 'static>));
-	window.request_animation_frame(cb.borrow().as_ref().unwrap().as_ref().unchecked_ref()).unwrap();
+	frame.set(Some(window.request_animation_frame(cb.borrow().as_ref().unwrap().as_ref().unchecked_ref()).unwrap()));
+	AnimationHandle { window: window.clone(), callback: cb, frame }
 }
 
 // basically just copy of rust std's dbg!
@@ -65,3 +175,16 @@ macro_rules! __dbg {
 	($val:expr,) => { $crate::dbg!($val) };
 	($($val:expr),+ $(,)?) => { ($($crate::dbg!($val)),+,) };
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn escapes_css_urls() {
+		assert_eq!(escape_css_url("a.png"), "a.png");
+		assert_eq!(escape_css_url(r#"a.png");}body{color:red"#), r#"a.png\");}body{color:red"#);
+		assert_eq!(escape_css_url("a\\\"\nb"), "a\\\\\\\"\\a b");
+		assert_eq!(css::background_image::url(escape_css_url("\"")).to_string(), r#"background-image:url("\"");"#);
+	}
+}
@@ -0,0 +1,31 @@
+use hobo::prelude::*;
+
+/// A dom event subscription for events hobo doesn't have a typed helper for (or that need capturing),
+/// unsubscribes from dom when dropped.
+pub struct EventListener {
+	target: web_sys::EventTarget,
+	name: &'static str,
+	capture: bool,
+	cb: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Drop for EventListener {
+	fn drop(&mut self) {
+		self.target.remove_event_listener_with_callback_and_bool(self.name, self.cb.as_ref().unchecked_ref(), self.capture).ok();
+	}
+}
+
+fn listen_impl<E: JsCast>(target: &web_sys::EventTarget, name: &'static str, capture: bool, mut f: impl FnMut(E) + 'static) -> EventListener {
+	let cb = Closure::wrap(Box::new(move |e: web_sys::Event| f(e.unchecked_into())) as Box<dyn FnMut(web_sys::Event) + 'static>);
+	target.add_event_listener_with_callback_and_bool(name, cb.as_ref().unchecked_ref(), capture).expect("can't add event listener");
+	EventListener { target: target.clone(), name, capture, cb }
+}
+
+pub fn listen<E: JsCast>(target: &web_sys::EventTarget, name: &'static str, f: impl FnMut(E) + 'static) -> EventListener {
+	listen_impl(target, name, false, f)
+}
+
+/// Same as `listen`, but during the capture phase - which is the only way to hear e.g. `scroll` of any element from the window.
+pub fn listen_capture<E: JsCast>(target: &web_sys::EventTarget, name: &'static str, f: impl FnMut(E) + 'static) -> EventListener {
+	listen_impl(target, name, true, f)
+}
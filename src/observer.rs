@@ -0,0 +1,106 @@
+use hobo::prelude::*;
+use super::closure_mut;
+
+/// Owns a `ResizeObserver` along with its callback, disconnects when dropped.
+pub struct ResizeObserverGuard {
+	observer: web_sys::ResizeObserver,
+	_closure: Closure<dyn FnMut(Vec<web_sys::ResizeObserverEntry>)>,
+}
+
+impl Drop for ResizeObserverGuard {
+	fn drop(&mut self) { self.observer.disconnect(); }
+}
+
+impl ResizeObserverGuard {
+	/// The underlying observer, e.g. for a callback to disconnect early.
+	pub fn observer(&self) -> &web_sys::ResizeObserver { &self.observer }
+}
+
+pub fn observe_resize(element: &web_sys::Element, f: impl FnMut(Vec<web_sys::ResizeObserverEntry>) + 'static) -> ResizeObserverGuard {
+	let closure = closure_mut(f);
+	let observer = web_sys::ResizeObserver::new(closure.as_ref().unchecked_ref()).unwrap();
+	observer.observe(element);
+	ResizeObserverGuard { observer, _closure: closure }
+}
+
+/// Owns an `IntersectionObserver` along with its callback, disconnects when dropped.
+pub struct IntersectionObserverGuard {
+	observer: web_sys::IntersectionObserver,
+	_closure: Closure<dyn FnMut(Vec<web_sys::IntersectionObserverEntry>)>,
+}
+
+impl Drop for IntersectionObserverGuard {
+	fn drop(&mut self) { self.observer.disconnect(); }
+}
+
+impl IntersectionObserverGuard {
+	/// The underlying observer, e.g. for a callback to disconnect early.
+	pub fn observer(&self) -> &web_sys::IntersectionObserver { &self.observer }
+}
+
+pub fn observe_intersection<'a>(
+	elements: impl IntoIterator<Item = &'a web_sys::Element>,
+	options: &web_sys::IntersectionObserverInit,
+	f: impl FnMut(Vec<web_sys::IntersectionObserverEntry>) + 'static,
+) -> IntersectionObserverGuard {
+	let closure = closure_mut(f);
+	let observer = web_sys::IntersectionObserver::new_with_options(closure.as_ref().unchecked_ref(), options).unwrap();
+	for element in elements { observer.observe(element); }
+	IntersectionObserverGuard { observer, _closure: closure }
+}
+
+/// Owns a `MutationObserver` along with its callback, disconnects when dropped.
+pub struct MutationObserverGuard {
+	observer: web_sys::MutationObserver,
+	_closure: Closure<dyn FnMut(Vec<web_sys::MutationRecord>)>,
+}
+
+impl Drop for MutationObserverGuard {
+	fn drop(&mut self) { self.observer.disconnect(); }
+}
+
+pub fn observe_mutations(node: &web_sys::Node, options: &web_sys::MutationObserverInit, f: impl FnMut(Vec<web_sys::MutationRecord>) + 'static) -> MutationObserverGuard {
+	let closure = closure_mut(f);
+	let observer = web_sys::MutationObserver::new(closure.as_ref().unchecked_ref()).unwrap();
+	observer.observe_with_options(node, options).unwrap();
+	MutationObserverGuard { observer, _closure: closure }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ObserverKind {
+	Intersection(web_sys::IntersectionObserver),
+	Resize(web_sys::ResizeObserver),
+}
+
+/// Stops and resumes an element's observer set up by `AsElementExt::set_on_intersection` or `set_on_content_resize`.
+///
+/// Doesn't keep the element alive, both methods do nothing once it's dead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObserverHandle {
+	element: hobo::Element,
+	observer: ObserverKind,
+}
+
+impl ObserverHandle {
+	pub(crate) fn intersection(element: hobo::Element, observer: web_sys::IntersectionObserver) -> Self { Self { element, observer: ObserverKind::Intersection(observer) } }
+	pub(crate) fn resize(element: hobo::Element, observer: web_sys::ResizeObserver) -> Self { Self { element, observer: ObserverKind::Resize(observer) } }
+
+	/// Stops observing, e.g. after the first time the element became visible.
+	pub fn disconnect(&self) {
+		if self.element.is_dead() { return; }
+		match &self.observer {
+			ObserverKind::Intersection(x) => x.disconnect(),
+			ObserverKind::Resize(x) => x.disconnect(),
+		}
+	}
+
+	/// Starts observing again after `disconnect`. The observer reports the element's current state right away, same as when it was first set up.
+	pub fn reconnect(&self) {
+		if self.element.is_dead() { return; }
+		let element = self.element.get_cmp::<web_sys::Element>();
+		match &self.observer {
+			ObserverKind::Intersection(x) => x.observe(&element),
+			ObserverKind::Resize(x) => x.observe(&element),
+		}
+	}
+}
@@ -0,0 +1,186 @@
+use std::{cell::Cell, rc::Rc};
+use hobo::prelude::*;
+use super::{request_frame_coalesced, Context};
+use super::entity_ext::AsEntityExt;
+use super::listener::{listen, listen_capture};
+use super::observer::observe_resize;
+
+/// Side of the reference element a popover goes on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+	Top,
+	Right,
+	Bottom,
+	Left,
+}
+
+impl Side {
+	pub fn opposite(self) -> Self {
+		match self {
+			Self::Top => Self::Bottom,
+			Self::Right => Self::Left,
+			Self::Bottom => Self::Top,
+			Self::Left => Self::Right,
+		}
+	}
+
+	// whether the popover is above/below the reference, so the cross axis is horizontal
+	fn is_vertical(self) -> bool { matches!(self, Self::Top | Self::Bottom) }
+}
+
+/// How a popover lines up with the reference element along its side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Alignment {
+	/// Left edges (or top edges on the left/right sides) line up.
+	Start,
+	#[default] Center,
+	End,
+}
+
+/// Configuration for `compute_position`, `Side::Top` with `Alignment::Start` is what Popper calls `top-start`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionOptions {
+	pub side: Side,
+	pub alignment: Alignment,
+	/// Gap between the reference and the popover in px.
+	pub offset: f64,
+	/// How close in px the popover may get to the viewport's edges.
+	pub padding: f64,
+	/// Move to the opposite side if the popover overflows the viewport on the preferred one but fits on the other.
+	pub flip: bool,
+	/// Slide along the side to stay in the viewport, though never so far as to detach from the reference.
+	pub shift: bool,
+}
+
+impl Default for PositionOptions {
+	fn default() -> Self { Self { side: Side::Bottom, alignment: Alignment::Center, offset: 8., padding: 8., flip: true, shift: true } }
+}
+
+/// Where `compute_position` puts the popover.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Placement {
+	/// Viewport coordinates of the popover's top left corner, i.e. its `left`/`top` if it's `position: fixed`.
+	pub x: f64,
+	pub y: f64,
+	/// The side it ended up on, the opposite of the requested one if it flipped.
+	pub side: Side,
+	/// Where an arrow should be, in px along the popover's edge facing the reference - from the left for top/bottom, from the top for left/right.
+	/// Points at the reference's center, but stays `padding` away from the popover's corners.
+	pub arrow: f64,
+}
+
+// without scrollbars, unlike `inner_width`/`inner_height`
+fn viewport_size(context: &Context) -> (f64, f64) {
+	match context.document.document_element() {
+		Some(root) => (f64::from(root.client_width()), f64::from(root.client_height())),
+		None => {
+			let dimension = |x: Result<JsValue, JsValue>| x.ok().and_then(|x| x.as_f64()).unwrap_or_default();
+			(dimension(context.window.inner_width()), dimension(context.window.inner_height()))
+		},
+	}
+}
+
+// the popover's coordinate along the main axis (y for top/bottom, x for left/right)
+fn main_axis(side: Side, reference: &web_sys::DomRect, (width, height): (f64, f64), offset: f64) -> f64 {
+	match side {
+		Side::Top => reference.top() - height - offset,
+		Side::Bottom => reference.bottom() + offset,
+		Side::Left => reference.left() - width - offset,
+		Side::Right => reference.right() + offset,
+	}
+}
+
+fn fits(side: Side, reference: &web_sys::DomRect, size: (f64, f64), options: &PositionOptions, (viewport_width, viewport_height): (f64, f64)) -> bool {
+	let main = main_axis(side, reference, size, options.offset);
+	match side {
+		Side::Top | Side::Left => main >= options.padding,
+		Side::Bottom => main + size.1 <= viewport_height - options.padding,
+		Side::Right => main + size.0 <= viewport_width - options.padding,
+	}
+}
+
+/// Positions `popover` next to `reference` as described by `options`, flipping and shifting it to stay within the viewport.
+///
+/// Works with the elements' current bounding rects, so the popover should already be rendered (even if invisible) to have a size.
+/// The viewport is that of the window the popover is in, see `Context::of`.
+/// See `position_signal` for keeping it up to date.
+pub fn compute_position(reference: &impl AsElement, popover: &impl AsElement, options: &PositionOptions) -> Placement {
+	let rect = reference.get_cmp::<web_sys::Element>().get_bounding_client_rect();
+	let popover_rect = popover.get_cmp::<web_sys::Element>().get_bounding_client_rect();
+	let size = (popover_rect.width(), popover_rect.height());
+	let viewport = viewport_size(&Context::of(popover));
+
+	let side = if options.flip && !fits(options.side, &rect, size, options, viewport) && fits(options.side.opposite(), &rect, size, options, viewport) {
+		options.side.opposite()
+	} else {
+		options.side
+	};
+
+	let (cross_size, cross_viewport, reference_start, reference_size) = if side.is_vertical() {
+		(size.0, viewport.0, rect.left(), rect.width())
+	} else {
+		(size.1, viewport.1, rect.top(), rect.height())
+	};
+	let mut cross = match options.alignment {
+		Alignment::Start => reference_start,
+		Alignment::Center => reference_start + (reference_size - cross_size) / 2.,
+		Alignment::End => reference_start + reference_size - cross_size,
+	};
+	if options.shift {
+		cross = f64::max(options.padding, f64::min(cross, cross_viewport - options.padding - cross_size));
+		cross = cross.clamp(reference_start - cross_size, reference_start + reference_size);
+	}
+
+	let arrow_padding = options.padding.min(cross_size / 2.);
+	let arrow = (reference_start + reference_size / 2. - cross).clamp(arrow_padding, cross_size - arrow_padding);
+	let main = main_axis(side, &rect, size, options.offset);
+	let (x, y) = if side.is_vertical() { (cross, main) } else { (main, cross) };
+	Placement { x, y, side, arrow }
+}
+
+/// Signal of `compute_position`, recomputed at most once per frame whenever anything scrolls, the window resizes or either element does.
+///
+/// Tracking stops when the popover dies.
+pub fn position_signal(reference: &impl AsElement, popover: &impl AsElement, options: PositionOptions) -> impl hobo::signal::Signal<Item = Placement> + 'static {
+	position_signal_in(reference, popover, options, Context::global())
+}
+
+/// Same as `position_signal`, but listening for scrolling and resizing of the `context`'s window.
+pub fn position_signal_in(reference: &impl AsElement, popover: &impl AsElement, options: PositionOptions, context: Context) -> impl hobo::signal::Signal<Item = Placement> + 'static {
+	let reference = reference.as_element();
+	let popover = popover.as_element();
+	let placement = hobo::signal::Mutable::new(compute_position(&reference, &popover, &options));
+	let pending = Rc::new(Cell::new(false));
+	let update = {
+		let placement = placement.clone();
+		Rc::new(move || {
+			let placement = placement.clone();
+			request_frame_coalesced(&pending, move || {
+				if reference.is_dead() || popover.is_dead() { return; }
+				placement.set_neq(compute_position(&reference, &popover, &options));
+			});
+		})
+	};
+
+	let window = &context.window;
+	popover.add_bundle(listen_capture(window, "scroll", #[clown::clown] move |_: web_sys::Event| honk!(update)()));
+	popover.add_bundle(listen(window, "resize", #[clown::clown] move |_: web_sys::Event| honk!(update)()));
+	popover.add_bundle(observe_resize(&reference.get_cmp::<web_sys::Element>(), #[clown::clown] move |_| honk!(update)()));
+	popover.add_bundle(observe_resize(&popover.get_cmp::<web_sys::Element>(), move |_| update()));
+
+	let signal = placement.signal();
+	popover.add_bundle(placement);
+	signal
+}
+
+/// Keeps `popover` positioned next to `reference` - makes it `position: fixed` and updates its inline `left`/`top` from `position_signal`.
+///
+/// Use `position_signal` directly for e.g. an arrow that has to follow along.
+pub fn anchor_popover(reference: &impl AsElement, popover: &impl AsElement, options: PositionOptions) {
+	let Some(style) = popover.get_cmp::<web_sys::Element>().dyn_ref::<web_sys::HtmlElement>().map(web_sys::HtmlElement::style) else { return; };
+	style.set_property("position", "fixed").ok();
+	popover.add_bundle(position_signal(reference, popover, options).subscribe(move |placement| {
+		style.set_property("left", &format!("{}px", placement.x)).ok();
+		style.set_property("top", &format!("{}px", placement.y)).ok();
+	}));
+}
@@ -0,0 +1,148 @@
+use std::cell::RefCell;
+use hobo::prelude::*;
+use super::window;
+
+/// Order in which scheduled callbacks run within a frame. `Critical` ones run every frame regardless of the budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+	Low,
+	Normal,
+	Critical,
+}
+
+/// How long (in ms) callbacks get to run in a single frame before the rest is deferred to the next one.
+pub const DEFAULT_FRAME_BUDGET_MS: f64 = 8.;
+
+struct Entry {
+	priority: Priority,
+	f: Box<dyn FnMut(f64) -> bool>,
+	last_timestamp: Option<f64>,
+	deferred: bool,
+}
+
+struct Scheduler {
+	budget_ms: f64,
+	entries: Vec<Entry>,
+	frame_requested: bool,
+}
+
+thread_local! {
+	static SCHEDULER: RefCell<Scheduler> = const { RefCell::new(Scheduler { budget_ms: DEFAULT_FRAME_BUDGET_MS, entries: Vec::new(), frame_requested: false }) };
+}
+
+pub fn set_frame_budget(budget_ms: f64) { SCHEDULER.with(|x| x.borrow_mut().budget_ms = budget_ms); }
+
+/// Like `animation`, but shares the frame with everything else scheduled here and keeps to a time budget per frame.
+///
+/// Each frame, callbacks run from the highest priority down, the ones deferred from the previous frame going first within a priority.
+/// Once the frame's budget is used up, the remaining non-`Critical` callbacks are deferred to the next frame,
+/// so a frame only overruns the budget by at most the one callback that crossed it (plus `Critical` ones).
+/// `f` receives the time since it last ran in ms and keeps getting called for as long as it returns `true`.
+pub fn schedule(priority: Priority, f: impl FnMut(f64) -> bool + 'static) {
+	SCHEDULER.with(|x| x.borrow_mut().entries.push(Entry { priority, f: Box::new(f), last_timestamp: None, deferred: false }));
+	request_frame();
+}
+
+fn request_frame() {
+	let requested = SCHEDULER.with(|x| std::mem::replace(&mut x.borrow_mut().frame_requested, true));
+	if requested { return; }
+	window().request_animation_frame(Closure::once_into_js(run_frame).unchecked_ref()).unwrap();
+}
+
+fn run_frame(timestamp: f64) {
+	let performance = window().performance().expect("no performance");
+	// taken out, so callbacks can schedule more in the meantime
+	let (mut entries, budget_ms) = SCHEDULER.with(|x| {
+		let mut scheduler = x.borrow_mut();
+		scheduler.frame_requested = false;
+		(std::mem::take(&mut scheduler.entries), scheduler.budget_ms)
+	});
+	run_entries(&mut entries, timestamp, budget_ms, || performance.now());
+
+	let pending = SCHEDULER.with(|x| {
+		let mut scheduler = x.borrow_mut();
+		entries.append(&mut scheduler.entries);
+		scheduler.entries = entries;
+		!scheduler.entries.is_empty()
+	});
+	if pending { request_frame(); }
+}
+
+// runs a frame's worth of `entries` within `budget_ms` as measured by `now`, dropping the finished ones and marking the ones that didn't get to run
+fn run_entries(entries: &mut Vec<Entry>, timestamp: f64, budget_ms: f64, now: impl Fn() -> f64) {
+	let start = now();
+	entries.sort_by(|a, b| b.priority.cmp(&a.priority).then(b.deferred.cmp(&a.deferred)));
+
+	entries.retain_mut(|entry| {
+		let Some(last_timestamp) = entry.last_timestamp else { entry.last_timestamp = Some(timestamp); return true; };
+		if entry.priority != Priority::Critical && now() - start > budget_ms {
+			entry.deferred = true;
+			return true;
+		}
+		entry.deferred = false;
+		entry.last_timestamp = Some(timestamp);
+		(entry.f)(timestamp - last_timestamp)
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{cell::Cell, rc::Rc};
+
+	// a callback that takes `cost_ms` on the fake `clock` and records its `id` every time it runs
+	fn entry(priority: Priority, id: usize, cost_ms: f64, clock: &Rc<Cell<f64>>, ran: &Rc<RefCell<Vec<usize>>>) -> Entry {
+		let (clock, ran) = (Rc::clone(clock), Rc::clone(ran));
+		Entry {
+			priority,
+			f: Box::new(move |_| { clock.set(clock.get() + cost_ms); ran.borrow_mut().push(id); true }),
+			last_timestamp: Some(0.),
+			deferred: false,
+		}
+	}
+
+	#[test]
+	fn overruns_by_at_most_one_callback() {
+		let clock = Rc::new(Cell::new(0.));
+		let ran = Rc::new(RefCell::new(Vec::new()));
+		let costs = [3., 5., 2., 4., 3., 6., 1.];
+		let mut entries = costs.iter().enumerate().map(|(id, &cost)| entry(Priority::Normal, id, cost, &clock, &ran)).collect::<Vec<_>>();
+
+		for frame in 1..=5 {
+			let start = clock.get();
+			ran.borrow_mut().clear();
+			run_entries(&mut entries, f64::from(frame) * 16., DEFAULT_FRAME_BUDGET_MS, || clock.get());
+			let elapsed = clock.get() - start;
+			let last_cost = ran.borrow().last().map_or(0., |&id| costs[id]);
+			assert!(elapsed - last_cost <= DEFAULT_FRAME_BUDGET_MS, "frame {frame} took {elapsed}ms");
+			assert!(!ran.borrow().is_empty(), "frame {frame} ran nothing");
+		}
+	}
+
+	#[test]
+	fn deferred_callbacks_go_first() {
+		let clock = Rc::new(Cell::new(0.));
+		let ran = Rc::new(RefCell::new(Vec::new()));
+		let mut entries = (0..4).map(|id| entry(Priority::Normal, id, 5., &clock, &ran)).collect::<Vec<_>>();
+
+		run_entries(&mut entries, 16., DEFAULT_FRAME_BUDGET_MS, || clock.get());
+		assert_eq!(*ran.borrow(), [0, 1]);
+		ran.borrow_mut().clear();
+		run_entries(&mut entries, 32., DEFAULT_FRAME_BUDGET_MS, || clock.get());
+		assert_eq!(*ran.borrow(), [2, 3]);
+	}
+
+	#[test]
+	fn critical_callbacks_ignore_the_budget() {
+		let clock = Rc::new(Cell::new(0.));
+		let ran = Rc::new(RefCell::new(Vec::new()));
+		let mut entries = vec![
+			entry(Priority::Critical, 0, 20., &clock, &ran),
+			entry(Priority::Critical, 1, 20., &clock, &ran),
+			entry(Priority::Low, 2, 1., &clock, &ran),
+		];
+
+		run_entries(&mut entries, 16., DEFAULT_FRAME_BUDGET_MS, || clock.get());
+		assert_eq!(*ran.borrow(), [0, 1]);
+	}
+}
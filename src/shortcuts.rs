@@ -0,0 +1,207 @@
+use std::{cell::Cell, str::FromStr};
+use hobo::{prelude::*, create as e, signal::{Mutable, SignalExt}};
+use super::document;
+use super::listener::{listen, EventListener};
+use super::stacking::{on_layer, LayerKind};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseKeyComboError {
+	#[error("Key combo has no key.")] MissingKey,
+	#[error("Unknown modifier '{0}'.")] UnknownModifier(String),
+}
+
+/// A key along with the modifiers that have to be held for it, parsed from e.g. `"Ctrl+Shift+K"` or `"?"`.
+///
+/// Modifiers are `Ctrl`, `Alt`, `Shift` and `Meta` (or `Cmd`), the last `+`-separated part is the key - so `"Ctrl++"` is Ctrl and plus.
+/// The key is compared with `KeyboardEvent.key` ignoring case, and Shift isn't checked for single punctuation characters
+/// since whether those need it depends on the keyboard layout.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+	pub key: String,
+	pub ctrl: bool,
+	pub alt: bool,
+	pub shift: bool,
+	pub meta: bool,
+}
+
+impl KeyCombo {
+	pub fn matches(&self, e: &web_sys::KeyboardEvent) -> bool {
+		let shift_agnostic = self.key.chars().count() == 1 && !self.key.chars().all(char::is_alphanumeric);
+		e.key().eq_ignore_ascii_case(&self.key)
+			&& e.ctrl_key() == self.ctrl
+			&& e.alt_key() == self.alt
+			&& e.meta_key() == self.meta
+			&& (shift_agnostic || e.shift_key() == self.shift)
+	}
+
+	fn has_command_modifier(&self) -> bool { self.ctrl || self.alt || self.meta }
+}
+
+impl FromStr for KeyCombo {
+	type Err = ParseKeyComboError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (modifiers, key) = match s.strip_suffix('+') {
+			Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest.strip_suffix('+').unwrap_or(rest), "+"),
+			_ => s.rsplit_once('+').unwrap_or(("", s)),
+		};
+		let key = key.trim();
+		if key.is_empty() { return Err(ParseKeyComboError::MissingKey); }
+
+		let mut combo = Self { key: key.to_owned(), ..Self::default() };
+		for modifier in modifiers.split('+').map(str::trim).filter(|x| !x.is_empty()) {
+			match modifier.to_ascii_lowercase().as_str() {
+				"ctrl" | "control" => combo.ctrl = true,
+				"alt" | "option" => combo.alt = true,
+				"shift" => combo.shift = true,
+				"meta" | "cmd" | "command" | "super" => combo.meta = true,
+				_ => return Err(ParseKeyComboError::UnknownModifier(modifier.to_owned())),
+			}
+		}
+		Ok(combo)
+	}
+}
+
+impl std::fmt::Display for KeyCombo {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for (held, name) in [(self.ctrl, "Ctrl"), (self.alt, "Alt"), (self.shift, "Shift"), (self.meta, "Meta")] {
+			if held { write!(f, "{name}+")?; }
+		}
+		match self.key.as_str() {
+			" " => write!(f, "Space"),
+			key if key.chars().count() == 1 => write!(f, "{}", key.to_uppercase()),
+			key => write!(f, "{key}"),
+		}
+	}
+}
+
+// plain keys shouldn't fire while the user is typing, combos with Ctrl/Alt/Meta still do
+fn is_typing(e: &web_sys::KeyboardEvent) -> bool {
+	e.target()
+		.and_then(|x| x.dyn_into::<web_sys::Element>().ok())
+		.and_then(|x| x.closest("input, textarea, select, [contenteditable]:not([contenteditable=\"false\"])").ok().flatten())
+		.is_some()
+}
+
+/// Calls `f` whenever `combo` is pressed anywhere in the document, until the returned listener is dropped.
+///
+/// Auto-repeat is ignored and the default action is prevented. Combos without Ctrl, Alt or Meta don't fire while focus is in a text field.
+/// The shortcut isn't listed anywhere, see `register` for that.
+pub fn on_key_combo(combo: KeyCombo, mut f: impl FnMut(web_sys::KeyboardEvent) + 'static) -> EventListener {
+	listen(&document(), "keydown", move |e: web_sys::KeyboardEvent| {
+		if e.repeat() || !combo.matches(&e) { return; }
+		if !combo.has_command_modifier() && is_typing(&e) { return; }
+		e.prevent_default();
+		f(e);
+	})
+}
+
+/// A shortcut as listed by `overlay`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shortcut {
+	pub combo: KeyCombo,
+	pub group: String,
+	pub description: String,
+}
+
+impl Shortcut {
+	pub fn new(combo: KeyCombo, group: impl Into<String>, description: impl Into<String>) -> Self {
+		Self { combo, group: group.into(), description: description.into() }
+	}
+}
+
+thread_local! {
+	static REGISTRY: Mutable<Vec<(u64, Shortcut)>> = Mutable::default();
+	static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Keeps a shortcut listed, unlists it when dropped.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Registration(u64);
+
+impl Drop for Registration {
+	fn drop(&mut self) {
+		REGISTRY.with(|x| x.lock_mut().retain(|(id, _)| *id != self.0));
+	}
+}
+
+/// Lists `shortcut` for as long as the returned registration lives. This only documents it, it's still up to the caller to handle it.
+pub fn register(shortcut: Shortcut) -> Registration {
+	let id = NEXT_ID.with(|x| x.replace(x.get() + 1));
+	REGISTRY.with(|x| x.lock_mut().push((id, shortcut)));
+	Registration(id)
+}
+
+/// Currently registered shortcuts in the order they were registered.
+pub fn registered() -> Vec<Shortcut> { REGISTRY.with(|x| x.lock_ref().iter().map(|(_, x)| x.clone()).collect()) }
+
+/// Signal of `registered`.
+pub fn registered_signal() -> impl hobo::signal::Signal<Item = Vec<Shortcut>> + 'static {
+	REGISTRY.with(|x| x.signal_ref(|x| x.iter().map(|(_, x)| x.clone()).collect()))
+}
+
+/// Group that `overlay` lists its own toggle under.
+pub const OVERLAY_GROUP: &str = "General";
+
+fn render_groups(shortcuts: Vec<Shortcut>) -> e::Div {
+	// groups appear in the order their first shortcut was registered
+	let mut groups = Vec::<(String, Vec<Shortcut>)>::new();
+	for shortcut in shortcuts {
+		match groups.iter_mut().find(|(group, _)| *group == shortcut.group) {
+			Some((_, shortcuts)) => shortcuts.push(shortcut),
+			None => groups.push((shortcut.group.clone(), vec![shortcut])),
+		}
+	}
+
+	e::div().children(groups.into_iter().map(|(group, shortcuts)| e::section()
+		.child(e::h2().text(group))
+		.child(e::dl().children(shortcuts.into_iter().flat_map(|x| [
+			e::dt().child(e::kbd().text(x.combo.to_string())).as_element(),
+			e::dd().text(x.description).as_element(),
+		])))
+	))
+}
+
+/// A "?"-style cheat sheet of every registered shortcut, grouped by `Shortcut::group`. Pressing `toggle` shows and hides it, Escape hides it too.
+///
+/// It's appended to `<body>` on its own modal layer, so it covers the app no matter where this is called, and stays until removed.
+/// Focus is trapped within it while it's shown, and `toggle` only hides it while that trap is the topmost one, so it never pops a trap pushed on top of it. The toggle itself is listed under `OVERLAY_GROUP`.
+/// Apart from being centered on screen it's unstyled - groups are `section`s with an `h2` and a `dl` of `kbd` combos and their descriptions.
+pub fn overlay(toggle: KeyCombo) -> e::Div {
+	struct ShortcutsOverlayTag;
+	let overlay = on_layer(e::div(), LayerKind::Modal)
+		.attr("role", "dialog")
+		.attr("aria-modal", "true")
+		.attr("aria-label", "Keyboard shortcuts")
+		.class_typed::<ShortcutsOverlayTag>(css::properties![
+			css::position::fixed,
+			css::top::pct(50),
+			css::left::pct(50),
+			css::transform::translate_xy(-50, -50),
+			css::max_height::vh(80),
+			css::overflow::auto,
+		])
+		.child_signal(registered_signal().map(render_groups))
+		.allow_no_parent();
+
+	let element = overlay.get_cmp::<web_sys::HtmlElement>().clone();
+	element.set_hidden(true);
+	match document().body() {
+		Some(body) => { body.append_child(&element).ok(); },
+		None => log::warn!("can't show the shortcuts overlay without a body"),
+	}
+
+	// toggled imperatively rather than through a signal so it's already visible by the time the focus trap focuses it
+	overlay
+		.component(register(Shortcut::new(toggle.clone(), OVERLAY_GROUP, "Show keyboard shortcuts")))
+		.component(on_key_combo(toggle, move |_| {
+			if element.hidden() {
+				element.set_hidden(false);
+				let escaped = element.clone();
+				super::focus::push_focus_trap(&element, move || escaped.set_hidden(true));
+			} else if super::focus::is_top_focus_trap(&element) {
+				element.set_hidden(true);
+				super::focus::pop_focus_trap();
+			}
+		}))
+}
@@ -1,79 +1,334 @@
-use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+use std::{cell::{Cell, RefCell}, collections::{HashMap, VecDeque}, rc::Rc};
+use futures::channel::oneshot;
 use wasm_bindgen_futures::js_sys;
 use serde::{Serialize, de::DeserializeOwned};
-use hobo::prelude::*;
+use hobo::{prelude::*, signal::Mutable};
 #[allow(unused_imports)] use super::{honk, slip};
+use super::backoff::Backoff;
 
-pub struct Socket<Out> {
+/// How messages are turned into the bytes of binary frames and back.
+pub trait Codec: 'static {
+	fn encode<T: Serialize>(value: &T) -> anyhow::Result<Vec<u8>>;
+	fn decode<T: DeserializeOwned>(bytes: &[u8]) -> anyhow::Result<T>;
+}
+
+/// Compact binary encoding, the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PostcardCodec;
+
+impl Codec for PostcardCodec {
+	fn encode<T: Serialize>(value: &T) -> anyhow::Result<Vec<u8>> { Ok(postcard::to_stdvec(value)?) }
+	fn decode<T: DeserializeOwned>(bytes: &[u8]) -> anyhow::Result<T> { Ok(postcard::from_bytes(bytes)?) }
+}
+
+/// UTF-8 JSON, still sent as binary frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+	fn encode<T: Serialize>(value: &T) -> anyhow::Result<Vec<u8>> { Ok(serde_json::to_vec(value)?) }
+	fn decode<T: DeserializeOwned>(bytes: &[u8]) -> anyhow::Result<T> { Ok(serde_json::from_slice(bytes)?) }
+}
+
+// calls `connect` after each `wait` until it succeeds, giving up as soon as the socket gets closed in the meantime -
+// a connection made regardless goes to `abandon`, so nothing lingers after `close`
+async fn reconnect<W, E: std::fmt::Debug, Fut: Future<Output = ()>>(closed: &Cell<bool>, mut wait: impl FnMut() -> Fut, mut connect: impl FnMut() -> Result<W, E>, abandon: impl FnOnce(W)) -> Option<W> {
+	loop {
+		wait().await;
+		if closed.get() { return None; }
+		match connect() {
+			Ok(new_ws) if closed.get() => { abandon(new_ws); return None; },
+			Ok(new_ws) => return Some(new_ws),
+			// this is very unlikely to happen
+			Err(e) => log::warn!("{e:?}"),
+		}
+	}
+}
+
+type TopicSubscribers<Msg> = HashMap<String, Vec<(u64, futures::channel::mpsc::UnboundedSender<Msg>)>>;
+type OnClose = Rc<RefCell<Option<Box<dyn FnMut(web_sys::CloseEvent)>>>>;
+
+/// Marks frames that belong to a `Socket::call` rather than to the regular message stream.
+///
+/// A call is sent as this tag followed by the postcard-encoded `id: u64` and the request encoded with the socket's `Codec`,
+/// the server is expected to reply with the tag followed by the same id and the encoded response.
+/// With the default `PostcardCodec` that's just a postcard-encoded `(id, request)`/`(id, response)`.
+pub const RPC_TAG: &[u8] = b"\0rpc";
+
+/// How long `Socket::call` waits for a response.
+pub const DEFAULT_CALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SocketError {
+	#[error("Socket is closed.")] Closed,
+	#[error("No response within the timeout.")] Timeout,
+	#[error("Failed to encode request: '{0}'.")] Encode(String),
+	#[error("Failed to decode response: '{0}'.")] Decode(String),
+}
+
+/// Marks frames that belong to `Topics`, see there for the wire format.
+pub const TOPIC_TAG: &[u8] = b"\0top";
+
+// implemented by `Topics` so the untyped socket can hand it the frames tagged with `TOPIC_TAG`
+trait TopicRouter {
+	fn route(&self, frame: &[u8]);
+	fn resubscribe_frames(&self) -> Vec<Vec<u8>>;
+}
+
+#[derive(Default)]
+struct PendingCalls {
+	next_id: u64,
+	senders: HashMap<u64, oneshot::Sender<Vec<u8>>>,
+}
+
+// owned by a call's future, so the sender doesn't linger in `PendingCalls` once nobody waits for the response anymore -
+// whether it timed out or the caller dropped the future
+struct PendingCallGuard {
+	pending_calls: std::rc::Weak<RefCell<PendingCalls>>,
+	id: u64,
+}
+
+impl Drop for PendingCallGuard {
+	fn drop(&mut self) {
+		if let Some(pending_calls) = self.pending_calls.upgrade() { pending_calls.borrow_mut().senders.remove(&self.id); }
+	}
+}
+
+/// How many of the most recent `Socket::call` round-trip times `SocketMetrics` keeps.
+pub const RPC_LATENCY_SAMPLES: usize = 100;
+
+/// Traffic counters of a `Socket`, shared with it - the fields keep updating as messages go through.
+#[derive(Clone, Default, Debug)]
+pub struct SocketMetrics {
+	pub messages_sent: Mutable<u64>,
+	pub bytes_sent: Mutable<u64>,
+	pub messages_received: Mutable<u64>,
+	pub bytes_received: Mutable<u64>,
+	/// Round-trip times in ms of the last `RPC_LATENCY_SAMPLES` successful calls, oldest first.
+	/// Calls that time out or fail to send aren't sampled.
+	pub rpc_latencies_ms: Mutable<VecDeque<f64>>,
+}
+
+/// Plain copy of `SocketMetrics` at one point in time.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct SocketMetricsSnapshot {
+	pub messages_sent: u64,
+	pub bytes_sent: u64,
+	pub messages_received: u64,
+	pub bytes_received: u64,
+	pub rpc_latencies_ms: Vec<f64>,
+}
+
+impl SocketMetricsSnapshot {
+	/// Mean of `rpc_latencies_ms`, `None` if no call has completed yet.
+	pub fn average_rpc_latency_ms(&self) -> Option<f64> {
+		if self.rpc_latencies_ms.is_empty() { return None; }
+		Some(self.rpc_latencies_ms.iter().sum::<f64>() / self.rpc_latencies_ms.len() as f64)
+	}
+}
+
+impl SocketMetrics {
+	fn record(messages: &Mutable<u64>, bytes: &Mutable<u64>, len: usize) {
+		*messages.lock_mut() += 1;
+		*bytes.lock_mut() += len as u64;
+	}
+
+	fn record_latency(&self, ms: f64) {
+		let mut latencies = self.rpc_latencies_ms.lock_mut();
+		if latencies.len() == RPC_LATENCY_SAMPLES { latencies.pop_front(); }
+		latencies.push_back(ms);
+	}
+
+	pub fn snapshot(&self) -> SocketMetricsSnapshot {
+		SocketMetricsSnapshot {
+			messages_sent: self.messages_sent.get(),
+			bytes_sent: self.bytes_sent.get(),
+			messages_received: self.messages_received.get(),
+			bytes_received: self.bytes_received.get(),
+			rpc_latencies_ms: self.rpc_latencies_ms.lock_ref().iter().copied().collect(),
+		}
+	}
+
+	/// Signal of `snapshot`, taken every `interval` rather than on every message so a busy socket doesn't rerender a diagnostics panel nonstop.
+	/// Starts with the current snapshot and stops ticking once dropped.
+	pub fn snapshot_signal(&self, interval: std::time::Duration) -> impl hobo::signal::Signal<Item = SocketMetricsSnapshot> + 'static {
+		use hobo::signal::SignalExt;
+
+		let initial = self.snapshot();
+		let ticks = futures::stream::unfold(self.clone(), move |metrics| async move {
+			async_timer::new_timer(interval).await;
+			Some((metrics.snapshot(), metrics))
+		});
+		hobo::signal::from_stream(ticks).map(move |x| x.unwrap_or_else(|| initial.clone())).dedupe_cloned()
+	}
+}
+
+/// How many messages a `Socket` holds onto while it's disconnected, and how many it queues over a `RateLimit`, before dropping the oldest.
+pub const MAX_BUFFERED_MESSAGES: usize = 10;
+
+/// What to do with messages sent over the rate limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum RateLimitOverflow {
+	/// Hold onto them and send once the rate allows, up to `MAX_BUFFERED_MESSAGES`.
+	#[default] Queue,
+	/// Discard them.
+	Drop,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RateLimit {
+	pub max_msgs_per_sec: std::num::NonZeroU32,
+	pub overflow: RateLimitOverflow,
+}
+
+#[derive(Default)]
+struct RateLimiter {
+	limit: Option<RateLimit>,
+	// timestamps of messages sent within the last second, only kept while there's a limit to check them against
+	sent_at: VecDeque<f64>,
+	queue: VecDeque<Vec<u8>>,
+	flushing: bool,
+}
+
+impl RateLimiter {
+	// how long until another message can go out, None if right now
+	fn wait_ms(&mut self, now: f64) -> Option<f64> {
+		let limit = self.limit?;
+		while self.sent_at.front().is_some_and(|&x| now - x >= 1000.) { self.sent_at.pop_front(); }
+		if self.sent_at.len() < limit.max_msgs_per_sec.get() as usize { return None; }
+		self.sent_at.front().map(|&x| x + 1000. - now)
+	}
+
+	fn enqueue(&mut self, bytes: Vec<u8>) {
+		self.queue.push_back(bytes);
+		if self.queue.len() > MAX_BUFFERED_MESSAGES {
+			log::warn!("rate limit queue is full, dropping the oldest message");
+			self.queue.pop_front();
+		}
+	}
+
+	fn record_sent(&mut self, now: f64) {
+		if self.limit.is_some() { self.sent_at.push_back(now); }
+	}
+}
+
+/// A WebSocket of messages encoded with `C` (postcard by default) that reconnects with backoff whenever the connection drops.
+///
+/// Dropping the `Socket` returned from `new`/`with_codec` closes the connection and stops reconnecting,
+/// the same as calling `close`.
+pub struct Socket<Out, C = PostcardCodec> {
 	ws: Rc<RefCell<web_sys::WebSocket>>,
 	// this should probably be bounded
-	message_buffer: Rc<RefCell<VecDeque<Out>>>,
+	message_buffer: Rc<RefCell<VecDeque<Vec<u8>>>>,
+	// set once the socket is closed on purpose, so onclose doesn't reconnect
+	closed: Rc<Cell<bool>>,
+	on_close: OnClose,
+	metrics: SocketMetrics,
+	rate_limiter: Rc<RefCell<RateLimiter>>,
+	pending_calls: Rc<RefCell<PendingCalls>>,
+	topic_router: Rc<RefCell<Option<Rc<dyn TopicRouter>>>>,
+	// the handles passed to on_open/on_message borrow the connection and must not close it when dropped
+	owner: bool,
+	_out: std::marker::PhantomData<fn(Out, C)>,
 }
 
-unsafe impl<Out> Send for Socket<Out> {}
-unsafe impl<Out> Sync for Socket<Out> {}
+unsafe impl<Out, C> Send for Socket<Out, C> {}
+unsafe impl<Out, C> Sync for Socket<Out, C> {}
 
 impl<Out: Serialize + 'static> Socket<Out> {
 	pub fn new<In: DeserializeOwned + 'static>(url: &str, on_open: fn(&Self), on_message: fn(&Self, In)) -> Self {
+		Self::with_codec(url, on_open, on_message)
+	}
+}
+
+impl<Out: Serialize + 'static, C: Codec> Socket<Out, C> {
+	/// Same as `new`, but with messages encoded by `C` - e.g. `Socket::<Out, JsonCodec>::with_codec(...)`.
+	pub fn with_codec<In: DeserializeOwned + 'static>(url: &str, on_open: fn(&Self), on_message: fn(&Self, In)) -> Self {
 		let ws = Rc::new(RefCell::new(web_sys::WebSocket::new(url).unwrap()));
 		let message_buffer = Rc::new(RefCell::new(VecDeque::new()));
-		let interval_secs = Rc::new(RefCell::new(std::time::Duration::from_secs(0)));
+		let backoff = Rc::new(RefCell::new(Backoff::new(std::time::Duration::from_secs(15), std::time::Duration::from_secs(60 * 10))));
+		let closed = Rc::new(Cell::new(false));
+		let on_close: OnClose = Rc::new(RefCell::new(None));
+		let metrics = SocketMetrics::default();
+		let rate_limiter = Rc::new(RefCell::new(RateLimiter::default()));
+		let pending_calls = Rc::new(RefCell::new(PendingCalls::default()));
+		let topic_router = Rc::new(RefCell::new(None::<Rc<dyn TopicRouter>>));
 
 		let onopen = Closure::<dyn Fn(web_sys::Event)>::new(#[clown::clown] |_: web_sys::Event| {
 			let Some(ws) = slip!(Rc::downgrade(&ws)).upgrade() else { return; };
 			let Some(message_buffer) = slip!(Rc::downgrade(&message_buffer)).upgrade() else { return; };
-			let Some(interval_secs) = slip!(Rc::downgrade(&interval_secs)).upgrade() else { return; };
+			let Some(backoff) = slip!(Rc::downgrade(&backoff)).upgrade() else { return; };
 
-			let this = Self { ws: Rc::clone(&ws), message_buffer: Rc::clone(&message_buffer) };
+			let this = Self { ws: Rc::clone(&ws), message_buffer: Rc::clone(&message_buffer), closed: Rc::clone(&honk!(closed)), on_close: Rc::clone(&honk!(on_close)), metrics: honk!(metrics).clone(), rate_limiter: Rc::clone(&honk!(rate_limiter)), pending_calls: Rc::clone(&honk!(pending_calls)), topic_router: Rc::clone(&honk!(topic_router)), owner: false, _out: std::marker::PhantomData };
 			on_open(&this);
 
+			// the server forgets subscriptions along with the connection
+			let router = this.topic_router.borrow().clone();
+			if let Some(router) = router { for frame in router.resubscribe_frames() { this.send_bytes(frame).ok(); } }
+
 			let buffer = std::mem::take(&mut message_buffer.borrow_mut() as &mut VecDeque<_>);
-			for msg in buffer { this.send(msg).ok(); }
+			for bytes in buffer { this.send_bytes(bytes).ok(); }
 
-			*interval_secs.borrow_mut() = std::time::Duration::from_secs(0);
+			backoff.borrow_mut().reset();
 		}).into_js_value();
 		let onmessage = Closure::<dyn Fn(web_sys::MessageEvent)>::new(#[clown::clown] |e: web_sys::MessageEvent| {
 			let Some(ws) = slip!(Rc::downgrade(&ws)).upgrade() else { return; };
 			let Some(message_buffer) = slip!(Rc::downgrade(&message_buffer)).upgrade() else { return; };
-			let Some(interval_secs) = slip!(Rc::downgrade(&interval_secs)).upgrade() else { return; };
+			let Some(backoff) = slip!(Rc::downgrade(&backoff)).upgrade() else { return; };
 
 			let u8_arr = js_sys::Uint8Array::new(&e.data());
-			let msg = match postcard::from_bytes::<In>(&u8_arr.to_vec()) {
+			let metrics = &honk!(metrics);
+			SocketMetrics::record(&metrics.messages_received, &metrics.bytes_received, u8_arr.length() as usize);
+			let bytes = u8_arr.to_vec();
+			if let Some(frame) = bytes.strip_prefix(RPC_TAG) {
+				match postcard::take_from_bytes::<u64>(frame) {
+					Ok((id, response)) => match honk!(pending_calls).borrow_mut().senders.remove(&id) {
+						Some(sender) => { sender.send(response.to_vec()).ok(); },
+						None => log::warn!("response to unknown or timed out call {id}"),
+					},
+					Err(e) => log::error!("Error deserializing call response: {e:?}"),
+				}
+				return;
+			}
+			if let Some(frame) = bytes.strip_prefix(TOPIC_TAG) {
+				let router = honk!(topic_router).borrow().clone();
+				match router {
+					Some(router) => router.route(frame),
+					None => log::warn!("topic message without a Topics layer"),
+				}
+				return;
+			}
+			let msg = match C::decode::<In>(&bytes) {
 				Ok(x) => x,
 				Err(e) => { log::error!("Error deserializing server message: {e:?}"); return; },
 			};
 
-			let this = Self { ws: Rc::clone(&ws), message_buffer: Rc::clone(&message_buffer) };
+			let this = Self { ws: Rc::clone(&ws), message_buffer: Rc::clone(&message_buffer), closed: Rc::clone(&honk!(closed)), on_close: Rc::clone(&honk!(on_close)), metrics: honk!(metrics).clone(), rate_limiter: Rc::clone(&honk!(rate_limiter)), pending_calls: Rc::clone(&honk!(pending_calls)), topic_router: Rc::clone(&honk!(topic_router)), owner: false, _out: std::marker::PhantomData };
 			on_message(&this, msg);
 
-			*interval_secs.borrow_mut() = std::time::Duration::from_secs(0);
+			backoff.borrow_mut().reset();
 		}).into_js_value();
-		let onclose = Closure::<dyn Fn(web_sys::CloseEvent)>::new(#[clown::clown] |_: web_sys::CloseEvent| {
+		let onclose = Closure::<dyn Fn(web_sys::CloseEvent)>::new(#[clown::clown] |e: web_sys::CloseEvent| {
+			if let Some(f) = slip!(Rc::downgrade(&on_close)).upgrade() && let Some(f) = f.borrow_mut().as_mut() { f(e); }
+			if honk!(closed).get() { return; }
+
 			let ws = slip!(Rc::downgrade(&ws)).clone();
-			let mut interval_secs = interval_secs.borrow_mut();
-
-			*interval_secs = (*interval_secs + std::time::Duration::from_secs(15)).min(std::time::Duration::from_secs(60 * 10));
-			log::info!("waiting for {interval_secs:?} before reconnecting");
-			let mut interval = async_timer::interval(*interval_secs);
-			drop(interval_secs);
-			wasm_bindgen_futures::spawn_local(async move { loop {
-				// log::info!("socket closed, try again");
-				interval.wait().await;
-				let Some(ws) = ws.upgrade() else { break; };
+			let Some(url) = ws.upgrade().map(|x| x.borrow().url()) else { return; };
+			let closed = Rc::clone(&honk!(closed));
+			let delay = backoff.borrow_mut().next_delay();
+			log::info!("waiting for {delay:?} before reconnecting");
+			wasm_bindgen_futures::spawn_local(async move {
+				let connect = || web_sys::WebSocket::new(&url);
+				let abandon = |x: web_sys::WebSocket| { x.close().ok(); };
+				let Some(new_ws) = reconnect(&closed, || async_timer::new_timer(delay), connect, abandon).await else { return; };
+				let Some(ws) = ws.upgrade() else { new_ws.close().ok(); return; };
 				let mut ws = ws.borrow_mut();
-				match web_sys::WebSocket::new(&ws.url()) {
-					Ok(new_ws) => {
-						new_ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
-						new_ws.set_onopen(ws.onopen().as_ref());
-						new_ws.set_onmessage(ws.onmessage().as_ref());
-						new_ws.set_onclose(ws.onclose().as_ref());
-						*ws = new_ws;
-						break;
-					},
-					// this is very unlikely to happen
-					Err(e) => log::warn!("{e:?}"),
-				}
-			} });
+				new_ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
+				new_ws.set_onopen(ws.onopen().as_ref());
+				new_ws.set_onmessage(ws.onmessage().as_ref());
+				new_ws.set_onclose(ws.onclose().as_ref());
+				*ws = new_ws;
+			});
 		}).into_js_value();
 
 		{
@@ -84,26 +339,383 @@ impl<Out: Serialize + 'static> Socket<Out> {
 			ws.set_onclose(Some(onclose.unchecked_ref()));
 		}
 
-		Self { ws, message_buffer }
+		Self { ws, message_buffer, closed, on_close, metrics, rate_limiter, pending_calls, topic_router, owner: true, _out: std::marker::PhantomData }
+	}
+
+	/// Counters of messages and bytes sent and received over this socket, including across reconnects,
+	/// and round-trip times of `call`s. See `SocketMetrics::snapshot` for a plain copy.
+	pub fn metrics(&self) -> SocketMetrics { self.metrics.clone() }
+
+	/// Limit how many messages go out per second, `None` to lift the limit.
+	/// Messages that are already queued keep getting sent at the new rate.
+	pub fn set_rate_limit(&self, limit: Option<RateLimit>) {
+		let mut rate_limiter = self.rate_limiter.borrow_mut();
+		rate_limiter.limit = limit;
+		if limit.is_none() { rate_limiter.sent_at.clear(); }
+		drop(rate_limiter);
+		self.flush_rate_limited();
+	}
+
+	/// Called whenever the connection closes, including drops that are followed by a reconnect.
+	/// `CloseEvent` exposes `code`, `reason` and `was_clean`.
+	pub fn on_close(&self, f: impl FnMut(web_sys::CloseEvent) + 'static) {
+		*self.on_close.borrow_mut() = Some(Box::new(f));
+	}
+
+	/// Closes the connection with a normal closure code and stops reconnecting.
+	#[culpa::throws(anyhow::Error)]
+	pub fn close(&self) { self.close_with(1000, "")?; }
+
+	/// Closes the connection with `code` and `reason` and stops reconnecting.
+	///
+	/// `code` must be either 1000 or within 3000..=4999 and `reason` no longer than 123 bytes, otherwise this errors and the socket stays open.
+	#[culpa::throws(anyhow::Error)]
+	pub fn close_with(&self, code: u16, reason: &str) {
+		self.ws.borrow().close_with_code_and_reason(code, reason).map_err(|e| anyhow::anyhow!("{e:?}"))?;
+		self.closed.set(true);
+		self.message_buffer.borrow_mut().clear();
+		self.pending_calls.borrow_mut().senders.clear();
+	}
+
+	pub fn is_closed(&self) -> bool { self.closed.get() }
+
+	#[culpa::throws(anyhow::Error)]
+	pub fn send(&self, msg: Out) { self.send_bytes(C::encode(&msg)?)?; }
+
+	/// Sends `req` tagged with a fresh id and resolves with the response carrying the same id, see `RPC_TAG` for the wire format.
+	/// Gives up after `DEFAULT_CALL_TIMEOUT`.
+	pub fn call<Req: Serialize, Resp: DeserializeOwned>(&self, req: Req) -> impl Future<Output = Result<Resp, SocketError>> + 'static {
+		self.call_with_timeout(req, DEFAULT_CALL_TIMEOUT)
+	}
+
+	pub fn call_with_timeout<Req: Serialize, Resp: DeserializeOwned>(&self, req: Req, timeout: std::time::Duration) -> impl Future<Output = Result<Resp, SocketError>> + 'static {
+		let (sender, receiver) = oneshot::channel();
+		let id = {
+			let mut pending_calls = self.pending_calls.borrow_mut();
+			let id = pending_calls.next_id;
+			pending_calls.next_id += 1;
+			id
+		};
+		let sent = postcard::to_extend(&id, RPC_TAG.to_vec()).map_err(anyhow::Error::from)
+			.and_then(|mut bytes| { bytes.extend(C::encode(&req)?); Ok(bytes) })
+			.map_err(|e| SocketError::Encode(e.to_string()))
+			.and_then(|bytes| {
+				if self.closed.get() { return Err(SocketError::Closed); }
+				self.pending_calls.borrow_mut().senders.insert(id, sender);
+				// failing to transmit buffers the call until reconnecting, so keep waiting
+				if let Err(e) = self.send_bytes(bytes) { log::warn!("{e:?}"); }
+				Ok(())
+			});
+		let guard = PendingCallGuard { pending_calls: Rc::downgrade(&self.pending_calls), id };
+		let metrics = self.metrics.clone();
+		let started_at = js_sys::Date::now();
+
+		async move {
+			let _guard = guard;
+			sent?;
+			let timer = async_timer::new_timer(timeout);
+			futures::pin_mut!(timer);
+			match futures::future::select(receiver, timer).await {
+				futures::future::Either::Left((Ok(bytes), _)) => {
+					metrics.record_latency(js_sys::Date::now() - started_at);
+					C::decode::<Resp>(&bytes).map_err(|e| SocketError::Decode(e.to_string()))
+				},
+				futures::future::Either::Left((Err(oneshot::Canceled), _)) => Err(SocketError::Closed),
+				futures::future::Either::Right(_) => Err(SocketError::Timeout),
+			}
+		}
+	}
+
+}
+
+// doesn't depend on how messages get encoded, so the topic layer can use these from Drop
+impl<Out, C> Socket<Out, C> {
+	// a handle to the same connection that doesn't close it when dropped
+	fn borrowed(&self) -> Self {
+		Self {
+			ws: Rc::clone(&self.ws),
+			message_buffer: Rc::clone(&self.message_buffer),
+			closed: Rc::clone(&self.closed),
+			on_close: Rc::clone(&self.on_close),
+			metrics: self.metrics.clone(),
+			rate_limiter: Rc::clone(&self.rate_limiter),
+			pending_calls: Rc::clone(&self.pending_calls),
+			topic_router: Rc::clone(&self.topic_router),
+			owner: false,
+			_out: std::marker::PhantomData,
+		}
+	}
+
+	fn is_open(&self) -> bool { !self.closed.get() && self.ws.borrow().ready_state() == web_sys::WebSocket::OPEN }
+
+	fn transmit(ws: &web_sys::WebSocket, metrics: &SocketMetrics, bytes: &[u8]) -> anyhow::Result<()> {
+		ws.send_with_u8_array(bytes).map_err(|e| anyhow::anyhow!("{e:?}"))?;
+		SocketMetrics::record(&metrics.messages_sent, &metrics.bytes_sent, bytes.len());
+		Ok(())
+	}
+
+	fn flush_rate_limited(&self) {
+		{
+			let mut rate_limiter = self.rate_limiter.borrow_mut();
+			if rate_limiter.flushing || rate_limiter.queue.is_empty() { return; }
+			rate_limiter.flushing = true;
+		}
+
+		let ws = Rc::downgrade(&self.ws);
+		let rate_limiter = Rc::downgrade(&self.rate_limiter);
+		let closed = Rc::clone(&self.closed);
+		let metrics = self.metrics.clone();
+		wasm_bindgen_futures::spawn_local(async move { loop {
+			let (Some(ws), Some(limiter)) = (ws.upgrade(), rate_limiter.upgrade()) else { break; };
+			let wait_ms = {
+				let mut limiter = limiter.borrow_mut();
+				if closed.get() { limiter.queue.clear(); }
+				if limiter.queue.is_empty() { limiter.flushing = false; break; }
+				if ws.borrow().ready_state() != web_sys::WebSocket::OPEN { Some(1000.) } else { limiter.wait_ms(js_sys::Date::now()) }
+			};
+			if let Some(wait_ms) = wait_ms {
+				drop((ws, limiter));
+				async_timer::new_timer(std::time::Duration::from_millis(wait_ms.ceil() as u64)).await;
+				continue;
+			}
+
+			let mut limiter = limiter.borrow_mut();
+			let Some(bytes) = limiter.queue.pop_front() else { continue; };
+			match Self::transmit(&ws.borrow(), &metrics, &bytes) {
+				Ok(()) => limiter.record_sent(js_sys::Date::now()),
+				Err(e) => log::warn!("failed to send rate limited message, dropping: {e:?}"),
+			}
+		} });
 	}
 
 	#[culpa::throws(anyhow::Error)]
-	pub fn send(&self, msg: Out) {
+	fn send_bytes(&self, bytes: Vec<u8>) {
+		if self.closed.get() { anyhow::bail!("socket is closed"); }
 		let ws = self.ws.borrow();
 		if ws.ready_state() != web_sys::WebSocket::OPEN {
 			log::warn!("failed to send, buffering: status is not web_sys::WebSocket::OPEN");
 			let mut message_buffer = self.message_buffer.borrow_mut();
-			message_buffer.push_back(msg);
-			if message_buffer.len() > 10 { message_buffer.pop_front(); }
+			message_buffer.push_back(bytes);
+			if message_buffer.len() > MAX_BUFFERED_MESSAGES { message_buffer.pop_front(); }
 			return;
 		}
-		let send_res = ws.send_with_u8_array(&postcard::to_stdvec(&msg)?).map_err(|e| anyhow::anyhow!("{e:?}"));
+		{
+			let mut rate_limiter = self.rate_limiter.borrow_mut();
+			if rate_limiter.wait_ms(js_sys::Date::now()).is_some() || !rate_limiter.queue.is_empty() {
+				match rate_limiter.limit.map(|x| x.overflow) {
+					Some(RateLimitOverflow::Drop) => log::warn!("rate limit exceeded, dropping message"),
+					_ => rate_limiter.enqueue(bytes),
+				}
+				drop(rate_limiter);
+				self.flush_rate_limited();
+				return;
+			}
+		}
+		let send_res = Self::transmit(&ws, &self.metrics, &bytes);
+		if send_res.is_ok() { self.rate_limiter.borrow_mut().record_sent(js_sys::Date::now()); }
 		if send_res.is_err() {
 			log::warn!("failed to send, buffering");
 			let mut message_buffer = self.message_buffer.borrow_mut();
-			message_buffer.push_back(msg);
-			if message_buffer.len() > 10 { message_buffer.pop_front(); }
+			message_buffer.push_back(bytes);
+			if message_buffer.len() > MAX_BUFFERED_MESSAGES { message_buffer.pop_front(); }
 		}
 		send_res?;
 	}
 }
+
+impl<Out, C> Drop for Socket<Out, C> {
+	fn drop(&mut self) {
+		if !self.owner || self.closed.get() { return; }
+		self.closed.set(true);
+		self.pending_calls.borrow_mut().senders.clear();
+		self.ws.borrow().close().ok();
+	}
+}
+
+#[derive(Serialize)]
+enum TopicFrame<'a, Msg> {
+	Subscribe(&'a str),
+	Unsubscribe(&'a str),
+	Publish(&'a str, Msg),
+}
+
+fn topic_frame<C: Codec, Msg: Serialize>(frame: &TopicFrame<'_, Msg>) -> anyhow::Result<Vec<u8>> {
+	let mut bytes = TOPIC_TAG.to_vec();
+	bytes.extend(C::encode(frame)?);
+	Ok(bytes)
+}
+
+struct TopicsInner<Msg, C> {
+	next_id: Cell<u64>,
+	subscribers: RefCell<TopicSubscribers<Msg>>,
+	_codec: std::marker::PhantomData<C>,
+}
+
+impl<Msg: Serialize + DeserializeOwned + Clone + 'static, C: Codec> TopicRouter for TopicsInner<Msg, C> {
+	fn route(&self, frame: &[u8]) {
+		let (topic, msg) = match C::decode::<(String, Msg)>(frame) {
+			Ok(x) => x,
+			Err(e) => { log::error!("Error deserializing topic message: {e:?}"); return; },
+		};
+		let mut subscribers = self.subscribers.borrow_mut();
+		let Some(senders) = subscribers.get_mut(&topic) else { return; };
+		senders.retain(|(_, sender)| sender.unbounded_send(msg.clone()).is_ok());
+	}
+
+	fn resubscribe_frames(&self) -> Vec<Vec<u8>> {
+		self.subscribers.borrow().keys()
+			.filter_map(|topic| topic_frame::<C, _>(&TopicFrame::<Msg>::Subscribe(topic)).ok())
+			.collect()
+	}
+}
+
+/// Pub/sub over a single `Socket` - any number of local subscribers per topic, routed by the topic name.
+///
+/// Topic traffic is tagged with `TOPIC_TAG` so it doesn't mix with regular messages.
+/// Outgoing frames are the tag followed by a `TopicFrame` encoded with the socket's `Codec` - `Subscribe(topic)`/`Unsubscribe(topic)`
+/// when the first local subscriber of a topic appears/the last one goes away, and `Publish(topic, msg)`.
+/// Incoming frames are expected to be the tag followed by an encoded `(topic: String, msg)`.
+/// Subscriptions are sent again whenever the socket reconnects.
+pub struct Topics<Out, Msg, C = PostcardCodec> {
+	socket: Socket<Out, C>,
+	inner: Rc<TopicsInner<Msg, C>>,
+}
+
+impl<Out: Serialize + 'static, Msg: Serialize + DeserializeOwned + Clone + 'static, C: Codec> Topics<Out, Msg, C> {
+	/// Routes the socket's topic traffic to the new `Topics`, replacing whichever was there before.
+	pub fn new(socket: &Socket<Out, C>) -> Self {
+		let inner = Rc::new(TopicsInner { next_id: Cell::new(0), subscribers: RefCell::new(HashMap::new()), _codec: std::marker::PhantomData });
+		if socket.topic_router.replace(Some(Rc::clone(&inner) as Rc<dyn TopicRouter>)).is_some() {
+			log::warn!("replacing the socket's previous Topics");
+		}
+		Self { socket: socket.borrowed(), inner }
+	}
+
+	/// Messages published to `topic`, for as long as the returned signal is alive.
+	pub fn subscribe(&self, topic: impl Into<String>) -> TopicSignal<Out, Msg, C> {
+		let topic = topic.into();
+		let id = self.inner.next_id.get();
+		self.inner.next_id.set(id + 1);
+		let (sender, receiver) = futures::channel::mpsc::unbounded();
+		let first = {
+			let mut subscribers = self.inner.subscribers.borrow_mut();
+			let senders = subscribers.entry(topic.clone()).or_default();
+			senders.push((id, sender));
+			senders.len() == 1
+		};
+		// while disconnected this is taken care of by resubscribing on open
+		if first && self.socket.is_open() && let Err(e) = topic_frame::<C, _>(&TopicFrame::<Msg>::Subscribe(&topic)).and_then(|x| self.socket.send_bytes(x)) {
+			log::warn!("failed to subscribe to {topic}: {e:?}");
+		}
+		TopicSignal { socket: self.socket.borrowed(), inner: Rc::clone(&self.inner), topic, id, receiver }
+	}
+
+	#[culpa::throws(anyhow::Error)]
+	pub fn publish(&self, topic: &str, msg: Msg) {
+		self.socket.send_bytes(topic_frame::<C, _>(&TopicFrame::Publish(topic, msg))?)?;
+	}
+}
+
+/// Signal of the messages on a topic, returned from `Topics::subscribe`. It first yields once a message arrives.
+///
+/// Dropping it unsubscribes.
+pub struct TopicSignal<Out, Msg, C: Codec = PostcardCodec> {
+	socket: Socket<Out, C>,
+	inner: Rc<TopicsInner<Msg, C>>,
+	topic: String,
+	id: u64,
+	receiver: futures::channel::mpsc::UnboundedReceiver<Msg>,
+}
+
+impl<Out, Msg, C: Codec> Unpin for TopicSignal<Out, Msg, C> {}
+
+impl<Out, Msg, C: Codec> hobo::signal::Signal for TopicSignal<Out, Msg, C> {
+	type Item = Msg;
+
+	fn poll_change(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Msg>> {
+		futures::StreamExt::poll_next_unpin(&mut self.receiver, cx)
+	}
+}
+
+impl<Out, Msg, C: Codec> Drop for TopicSignal<Out, Msg, C> {
+	fn drop(&mut self) {
+		let last = {
+			let mut subscribers = self.inner.subscribers.borrow_mut();
+			let Some(senders) = subscribers.get_mut(&self.topic) else { return; };
+			senders.retain(|(id, _)| *id != self.id);
+			let last = senders.is_empty();
+			if last { subscribers.remove(&self.topic); }
+			last
+		};
+		if !last || !self.socket.is_open() { return; }
+		if let Err(e) = topic_frame::<C, _>(&TopicFrame::<()>::Unsubscribe(&self.topic)).and_then(|x| self.socket.send_bytes(x)) {
+			log::warn!("failed to unsubscribe from {}: {e:?}", self.topic);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sends_are_only_recorded_under_a_limit() {
+		let mut limiter = RateLimiter::default();
+		for i in 0..100 { limiter.record_sent(f64::from(i)); }
+		assert!(limiter.sent_at.is_empty());
+		assert_eq!(limiter.wait_ms(100.), None);
+
+		limiter.limit = Some(RateLimit { max_msgs_per_sec: std::num::NonZeroU32::new(2).unwrap(), overflow: RateLimitOverflow::Queue });
+		limiter.record_sent(0.);
+		limiter.record_sent(10.);
+		assert_eq!(limiter.wait_ms(500.), Some(500.));
+		assert_eq!(limiter.wait_ms(1000.), None);
+		assert_eq!(limiter.sent_at.len(), 1);
+	}
+
+	#[test]
+	fn queue_drops_the_oldest_when_full() {
+		let mut limiter = RateLimiter::default();
+		for i in 0..MAX_BUFFERED_MESSAGES as u8 + 3 { limiter.enqueue(vec![i]); }
+		assert_eq!(limiter.queue.len(), MAX_BUFFERED_MESSAGES);
+		assert_eq!(limiter.queue.front(), Some(&vec![3]));
+	}
+
+	#[test]
+	fn reconnects_after_failed_attempts() {
+		let closed = Cell::new(false);
+		let mut attempts = 0;
+		let connect = || { attempts += 1; if attempts < 3 { Err("refused") } else { Ok(attempts) } };
+		let reconnected = futures::executor::block_on(reconnect(&closed, || async {}, connect, |_| panic!("nothing to abandon")));
+		assert_eq!(reconnected, Some(3));
+	}
+
+	#[test]
+	fn closing_while_waiting_stops_reconnecting() {
+		let closed = Cell::new(false);
+		let connected = Cell::new(false);
+		let wait = || { closed.set(true); async {} };
+		let connect = || { connected.set(true); Ok::<_, ()>(()) };
+		let reconnected = futures::executor::block_on(reconnect(&closed, wait, connect, |()| {}));
+		assert_eq!(reconnected, None);
+		assert!(!connected.get());
+	}
+
+	#[test]
+	fn closing_while_connecting_abandons_the_connection() {
+		let closed = Cell::new(false);
+		let abandoned = Cell::new(None);
+		let connect = || { closed.set(true); Ok::<_, ()>(7) };
+		let reconnected = futures::executor::block_on(reconnect(&closed, || async {}, connect, |x| abandoned.set(Some(x))));
+		assert_eq!(reconnected, None);
+		assert_eq!(abandoned.get(), Some(7));
+	}
+
+	#[test]
+	fn dropping_a_call_forgets_its_sender() {
+		let pending_calls = Rc::new(RefCell::new(PendingCalls { next_id: 1, senders: HashMap::new() }));
+		pending_calls.borrow_mut().senders.insert(0, oneshot::channel().0);
+		drop(PendingCallGuard { pending_calls: Rc::downgrade(&pending_calls), id: 0 });
+		assert!(pending_calls.borrow().senders.is_empty());
+	}
+}
@@ -0,0 +1,118 @@
+use std::{cell::RefCell, rc::Rc};
+use super::animation;
+
+// below these the spring is considered at rest and snaps to the target
+const REST_DISPLACEMENT: f64 = 0.01;
+const REST_VELOCITY: f64 = 0.01;
+// longer frames (e.g. a background tab) are integrated as this to keep things stable
+const MAX_FRAME_SECS: f64 = 0.064;
+const STEP_SECS: f64 = 1. / 240.;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpringConfig {
+	pub stiffness: f64,
+	pub damping: f64,
+	pub mass: f64,
+}
+
+impl Default for SpringConfig {
+	fn default() -> Self { Self { stiffness: 170., damping: 26., mass: 1. } }
+}
+
+struct SpringState {
+	config: SpringConfig,
+	value: f64,
+	velocity: f64,
+	target: f64,
+	running: bool,
+}
+
+impl SpringState {
+	// advances by `secs`, returns whether the spring is still moving
+	fn step(&mut self, secs: f64) -> bool {
+		let SpringConfig { stiffness, damping, mass } = self.config;
+		let mut remaining = secs.min(MAX_FRAME_SECS);
+		while remaining > 0. {
+			let dt = remaining.min(STEP_SECS);
+			let force = -stiffness * (self.value - self.target) - damping * self.velocity;
+			self.velocity += force / mass.max(f64::EPSILON) * dt;
+			self.value += self.velocity * dt;
+			remaining -= dt;
+		}
+		let at_rest = (self.value - self.target).abs() < REST_DISPLACEMENT && self.velocity.abs() < REST_VELOCITY;
+		if at_rest {
+			self.value = self.target;
+			self.velocity = 0.;
+		}
+		!at_rest
+	}
+}
+
+/// A value that moves towards its target with damped spring physics, calling `f` with the new value every frame while it moves.
+///
+/// The target can be changed mid-flight, the motion carries on from the current value and velocity without a jump.
+#[derive(Clone)]
+pub struct SpringValue {
+	state: Rc<RefCell<SpringState>>,
+	// kept apart from the state so `f` can read the `SpringValue`
+	f: Rc<RefCell<dyn FnMut(f64)>>,
+}
+
+impl SpringValue {
+	pub fn new(config: SpringConfig, value: f64, f: impl FnMut(f64) + 'static) -> Self {
+		Self {
+			state: Rc::new(RefCell::new(SpringState { config, value, velocity: 0., target: value, running: false })),
+			f: Rc::new(RefCell::new(f)),
+		}
+	}
+
+	pub fn value(&self) -> f64 { self.state.borrow().value }
+	pub fn velocity(&self) -> f64 { self.state.borrow().velocity }
+	pub fn target(&self) -> f64 { self.state.borrow().target }
+
+	/// Starts moving towards `target`, or redirects the ongoing motion there.
+	pub fn set_target(&self, target: f64) {
+		let mut state = self.state.borrow_mut();
+		state.target = target;
+		if std::mem::replace(&mut state.running, true) { return; }
+		drop(state);
+
+		let state = Rc::clone(&self.state);
+		let f = Rc::clone(&self.f);
+		animation(move |delta_t| {
+			let (value, moving) = {
+				let mut state = state.borrow_mut();
+				let moving = state.step(delta_t / 1000.);
+				state.running = moving;
+				(state.value, moving)
+			};
+			(*f).borrow_mut()(value);
+			moving
+		});
+	}
+
+	/// Kicks the value with `velocity` (units per second), e.g. to carry on the motion of a released drag.
+	pub fn set_velocity(&self, velocity: f64) {
+		self.state.borrow_mut().velocity = velocity;
+		let target = self.target();
+		self.set_target(target);
+	}
+
+	/// Jumps to `value` and stops there.
+	pub fn set_value(&self, value: f64) {
+		{
+			let mut state = self.state.borrow_mut();
+			state.value = value;
+			state.target = value;
+			state.velocity = 0.;
+		}
+		(*self.f).borrow_mut()(value);
+	}
+}
+
+/// Animates from `from` to `to` with spring physics on the `animation` loop, see `SpringValue`.
+pub fn spring(config: SpringConfig, from: f64, to: f64, f: impl FnMut(f64) + 'static) -> SpringValue {
+	let value = SpringValue::new(config, from, f);
+	value.set_target(to);
+	value
+}
@@ -0,0 +1,121 @@
+use std::{cell::RefCell, collections::BTreeMap};
+use hobo::prelude::*;
+
+/// Overlay categories, each gets its own band of z-index values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LayerKind {
+	Toast,
+	Popover,
+	Modal,
+}
+
+// z-index of the bottom of the lowest band, each band is BAND_SIZE wide
+const BASE_Z_INDEX: u32 = 1000;
+const BAND_SIZE: u32 = 1000;
+
+struct Stacking {
+	// lowest first
+	order: [LayerKind; 3],
+	// slots within the band of each kind and how many live layers hold them, indexed by `LayerKind as usize`
+	// only the top slot is ever held by more than one, once a band runs out
+	taken: [BTreeMap<u32, u32>; 3],
+}
+
+impl Stacking {
+	fn band(&self, kind: LayerKind) -> usize { self.order.iter().position(|&x| x == kind).unwrap_or_default() }
+}
+
+thread_local! {
+	static STACKING: RefCell<Stacking> = RefCell::new(Stacking {
+		order: [LayerKind::Toast, LayerKind::Popover, LayerKind::Modal],
+		taken: Default::default(),
+	});
+}
+
+/// Changes which kinds of overlays go on top of which, lowest first. Only affects layers issued afterwards.
+pub fn set_order(order: [LayerKind; 3]) {
+	STACKING.with(|x| {
+		x.borrow_mut().order = order;
+	});
+}
+
+/// A z-index issued by `next_layer`, released for reuse when dropped.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Layer {
+	kind: LayerKind,
+	slot: u32,
+	z_index: u32,
+}
+
+impl Layer {
+	pub fn kind(&self) -> LayerKind { self.kind }
+	pub fn z_index(&self) -> u32 { self.z_index }
+}
+
+impl Drop for Layer {
+	fn drop(&mut self) {
+		STACKING.with(|x| {
+			let taken = &mut x.borrow_mut().taken[self.kind as usize];
+			let Some(holders) = taken.get_mut(&self.slot) else { return; };
+			*holders -= 1;
+			if *holders == 0 { taken.remove(&self.slot); }
+		});
+	}
+}
+
+/// A z-index above every other live layer of the same `kind`, and above/below all layers of kinds ordered below/above it.
+pub fn next_layer(kind: LayerKind) -> Layer {
+	STACKING.with(|x| {
+		let mut stacking = x.borrow_mut();
+		let band = stacking.band(kind);
+		let taken = &mut stacking.taken[kind as usize];
+		let slot = match taken.last_key_value() {
+			None => 0,
+			Some((&top, _)) if top < BAND_SIZE - 1 => top + 1,
+			Some((&top, _)) => { log::warn!("ran out of z-indices for {kind:?} layers, sharing the topmost one"); top },
+		};
+		*taken.entry(slot).or_default() += 1;
+		Layer { kind, slot, z_index: BASE_Z_INDEX + band as u32 * BAND_SIZE + slot }
+	})
+}
+
+/// Puts the element on a fresh layer of `kind`, which is released when the element dies.
+pub fn on_layer<E: AsElement>(element: E, kind: LayerKind) -> E {
+	struct LayerTag;
+	let layer = next_layer(kind);
+	element
+		.class_typed::<LayerTag>(css::properties![css::z_index::Number(layer.z_index() as i32)])
+		.component(layer)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn layers_stack_within_their_band() {
+		let a = next_layer(LayerKind::Popover);
+		let b = next_layer(LayerKind::Popover);
+		let modal = next_layer(LayerKind::Modal);
+		assert_eq!(b.z_index(), a.z_index() + 1);
+		assert!(modal.z_index() > b.z_index() + BAND_SIZE - 2);
+		drop(b);
+		assert_eq!(next_layer(LayerKind::Popover).z_index(), a.z_index() + 1);
+	}
+
+	#[test]
+	fn the_shared_top_slot_is_held_until_its_last_layer_drops() {
+		let layers = (0..BAND_SIZE + 2).map(|_| next_layer(LayerKind::Toast)).collect::<Vec<_>>();
+		let top = layers.last().unwrap().z_index();
+		assert_eq!(layers[layers.len() - 3].z_index(), top);
+		let mut layers = layers.into_iter().rev();
+		drop(layers.next());
+		drop(layers.next());
+		// one layer still holds the top slot, so it isn't freed and the next layer shares it again
+		assert_eq!(next_layer(LayerKind::Toast).z_index(), top);
+		drop(layers.next());
+		assert_eq!(next_layer(LayerKind::Toast).z_index(), top);
+		drop(layers.next());
+		assert_eq!(next_layer(LayerKind::Toast).z_index(), top - 1);
+	}
+}
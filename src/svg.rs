@@ -1,5 +1,9 @@
 pub use crate::document;
 
+/// Builds the svg element for `xml_node` as is - only use on trusted input.
+///
+/// Scripts, `on*` event handler attributes and external references all make it into the DOM,
+/// so user-supplied svg must go through `xml_to_svg_sanitized` instead.
 pub fn xml_to_svg(xml_node: &roxmltree::Node) -> web_sys::SvgElement {
 	let html_node: web_sys::SvgElement = wasm_bindgen::JsCast::unchecked_into(document().create_element_ns(Some(wasm_bindgen::intern("http://www.w3.org/2000/svg")), xml_node.tag_name().name()).unwrap());
 	for attribute in xml_node.attributes() {
@@ -16,14 +20,514 @@ pub fn xml_to_svg(xml_node: &roxmltree::Node) -> web_sys::SvgElement {
 	html_node
 }
 
+/// An svg's `viewBox` - the region of user space that gets mapped onto the viewport.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewBox {
+	pub min_x: f64,
+	pub min_y: f64,
+	pub width: f64,
+	pub height: f64,
+}
+
+impl std::str::FromStr for ViewBox {
+	type Err = ();
+
+	/// Parses a `viewBox` attribute value - four numbers separated by whitespace and/or commas.
+	fn from_str(s: &str) -> Result<Self, ()> {
+		let mut numbers = s.split(|c: char| c.is_whitespace() || c == ',').filter(|x| !x.is_empty()).map(str::parse::<f64>);
+		let mut next = || numbers.next().and_then(Result::ok).ok_or(());
+		let view_box = Self { min_x: next()?, min_y: next()?, width: next()?, height: next()? };
+		if numbers.next().is_some() || view_box.width < 0. || view_box.height < 0. { return Err(()); }
+		Ok(view_box)
+	}
+}
+
+impl std::fmt::Display for ViewBox {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {} {} {}", self.min_x, self.min_y, self.width, self.height)
+	}
+}
+
+impl ViewBox {
+	/// Reads the element's `viewBox` attribute, `None` if it's missing or malformed.
+	pub fn parse(svg: &web_sys::Element) -> Option<Self> {
+		svg.get_attribute("viewBox")?.parse().ok()
+	}
+}
+
+pub fn set_view_box(svg: &web_sys::Element, view_box: ViewBox) {
+	svg.set_attribute(wasm_bindgen::intern("viewBox"), &view_box.to_string()).unwrap();
+}
+
+/// How `fit_to_container` fits the `viewBox` into the container.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FitMode {
+	/// Show the whole `viewBox`, letterboxing if the aspect ratios differ.
+	Contain,
+	/// Fill the whole container, cropping the `viewBox` if the aspect ratios differ.
+	Cover,
+}
+
+/// Sizes the svg to `width` x `height` and centers its `viewBox` in it according to `mode`.
+///
+/// An svg without a `viewBox` gets one from its current size first, since otherwise there's nothing to scale.
+pub fn fit_to_container(svg: &web_sys::Element, width: f64, height: f64, mode: FitMode) {
+	if ViewBox::parse(svg).is_none() {
+		let rect = svg.get_bounding_client_rect();
+		set_view_box(svg, ViewBox { min_x: 0., min_y: 0., width: rect.width(), height: rect.height() });
+	}
+	svg.set_attribute(wasm_bindgen::intern("width"), &width.to_string()).unwrap();
+	svg.set_attribute(wasm_bindgen::intern("height"), &height.to_string()).unwrap();
+	let preserve_aspect_ratio = match mode {
+		FitMode::Contain => "xMidYMid meet",
+		FitMode::Cover => "xMidYMid slice",
+	};
+	svg.set_attribute(wasm_bindgen::intern("preserveAspectRatio"), preserve_aspect_ratio).unwrap();
+}
+
+/// Which elements and attributes `xml_to_svg_sanitized` keeps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgAllowlist {
+	pub elements: std::collections::HashSet<&'static str>,
+	/// `None` allows any attribute, though `on*` attributes and external references are removed regardless.
+	pub attributes: Option<std::collections::HashSet<&'static str>>,
+}
+
+impl Default for SvgAllowlist {
+	fn default() -> Self {
+		Self {
+			elements: [
+				"svg", "g", "defs", "symbol", "use", "title", "desc",
+				"path", "rect", "circle", "ellipse", "line", "polyline", "polygon",
+				"text", "tspan", "textPath",
+				"linearGradient", "radialGradient", "stop", "pattern", "clipPath", "mask", "marker",
+				"filter", "feBlend", "feColorMatrix", "feComposite", "feFlood", "feGaussianBlur", "feMerge", "feMergeNode", "feOffset",
+			].into_iter().collect(),
+			attributes: None,
+		}
+	}
+}
+
+// only same-document references like `#id` and `url(#id)` are allowed, css function names are case-insensitive so `URL(` counts too
+fn is_external_reference(name: &str, value: &str) -> bool {
+	let value = value.trim().to_ascii_lowercase();
+	if name == "href" || name == "src" { return !value.starts_with('#'); }
+	value.split("url(").skip(1).any(|x| !x.trim_start_matches(['"', '\'', ' ']).starts_with('#'))
+}
+
+fn is_allowed_attribute(allowlist: &SvgAllowlist, name: &str, value: &str) -> bool {
+	!name.to_ascii_lowercase().starts_with("on")
+		&& !is_external_reference(name, value)
+		&& allowlist.attributes.as_ref().is_none_or(|x| x.contains(name))
+}
+
+// what's left of an xml tree after sanitizing, worked out separately from building the DOM so it can be checked without a browser
+#[derive(Debug, PartialEq, Eq)]
+enum SanitizedNode<'a> {
+	Element { name: &'a str, attributes: Vec<(&'a str, &'a str)>, children: Vec<SanitizedNode<'a>> },
+	Text(&'a str),
+}
+
+fn sanitize<'a>(xml_node: &roxmltree::Node<'a, '_>, allowlist: &SvgAllowlist) -> Option<SanitizedNode<'a>> {
+	let name = xml_node.tag_name().name();
+	if !allowlist.elements.contains(name) { return None; }
+	let attributes = xml_node.attributes()
+		.filter(|x| is_allowed_attribute(allowlist, x.name(), x.value()))
+		.map(|x| (x.name(), x.value()))
+		.collect();
+	let children = xml_node.children().filter_map(|child| {
+		if child.is_element() { sanitize(&child, allowlist) } else if child.is_text() { child.text().map(SanitizedNode::Text) } else { None }
+	}).collect();
+	Some(SanitizedNode::Element { name, attributes, children })
+}
+
+fn build_sanitized(node: &SanitizedNode) -> web_sys::Node {
+	match node {
+		SanitizedNode::Element { name, attributes, children } => {
+			let html_node = document().create_element_ns(Some(wasm_bindgen::intern("http://www.w3.org/2000/svg")), name).unwrap();
+			for (name, value) in attributes {
+				html_node.set_attribute(wasm_bindgen::intern(name), value).unwrap();
+			}
+			for child in children {
+				html_node.append_child(&build_sanitized(child)).unwrap();
+			}
+			html_node.into()
+		},
+		SanitizedNode::Text(text) => document().create_text_node(text).into(),
+	}
+}
+
+/// Same as `xml_to_svg`, but safe for untrusted input - keeps only the elements of the default `SvgAllowlist`,
+/// and drops `on*` event handler attributes as well as references to anything outside the document (`href`s and `url()`s that don't start with `#`).
+pub fn xml_to_svg_sanitized(xml_node: &roxmltree::Node) -> Option<web_sys::SvgElement> {
+	xml_to_svg_with_allowlist(xml_node, &SvgAllowlist::default())
+}
+
+/// `xml_to_svg_sanitized` with a custom allowlist. Returns `None` if the root element itself isn't allowed.
+pub fn xml_to_svg_with_allowlist(xml_node: &roxmltree::Node, allowlist: &SvgAllowlist) -> Option<web_sys::SvgElement> {
+	sanitize(xml_node, allowlist).map(|x| wasm_bindgen::JsCast::unchecked_into(build_sanitized(&x)))
+}
+
+const fn starts_at(haystack: &[u8], at: usize, needle: &[u8]) -> bool {
+	if at + needle.len() > haystack.len() { return false; }
+	let mut j = 0;
+	while j < needle.len() {
+		if haystack[at + j] != needle[j] { return false; }
+		j += 1;
+	}
+	true
+}
+
+const fn find(haystack: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+	let mut i = from;
+	while i + needle.len() <= haystack.len() {
+		if starts_at(haystack, i, needle) { return Some(i); }
+		i += 1;
+	}
+	None
+}
+
+const fn same_bytes(b: &[u8], a: usize, other: usize, len: usize) -> bool {
+	let mut k = 0;
+	while k < len {
+		if b[a + k] != b[other + k] { return false; }
+		k += 1;
+	}
+	true
+}
+
+const fn is_space(c: u8) -> bool { matches!(c, b' ' | b'\t' | b'\n' | b'\r') }
+const fn is_name_byte(c: u8) -> bool { c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.' | b':') || c >= 0x80 }
+
+const fn skip_spaces(b: &[u8], mut at: usize) -> usize {
+	while at < b.len() && is_space(b[at]) { at += 1; }
+	at
+}
+
+// length of the name starting at `at`, 0 if there's none
+const fn name_len(b: &[u8], at: usize) -> usize {
+	if at >= b.len() || b[at].is_ascii_digit() || b[at] == b'-' || b[at] == b'.' { return 0; }
+	let mut j = at;
+	while j < b.len() && is_name_byte(b[j]) { j += 1; }
+	j - at
+}
+
+// whether `xmlns:prefix` is declared anywhere, which is close enough to it being in scope for files that are otherwise well-formed
+const fn is_declared_prefix(b: &[u8], name: usize, len: usize) -> bool {
+	let mut prefix_len = 0;
+	while prefix_len < len && b[name + prefix_len] != b':' { prefix_len += 1; }
+	if prefix_len == len { return true; }
+	if prefix_len == 0 || prefix_len + 1 == len { return false; }
+	if (prefix_len == 3 && starts_at(b, name, b"xml")) || (prefix_len == 5 && starts_at(b, name, b"xmlns")) { return true; }
+	let mut from = 0;
+	while let Some(x) = find(b, from, b"xmlns:") {
+		let declared = x + b"xmlns:".len();
+		if declared + prefix_len < b.len() && same_bytes(b, declared, name, prefix_len) && !is_name_byte(b[declared + prefix_len]) { return true; }
+		from = declared;
+	}
+	false
+}
+
+// the five predefined entities, a doctype that could declare others isn't allowed anyway
+const fn is_predefined_entity(b: &[u8], name: usize, len: usize) -> bool {
+	let predefined: [&[u8]; 5] = [b"amp", b"lt", b"gt", b"quot", b"apos"];
+	let mut k = 0;
+	while k < predefined.len() {
+		if predefined[k].len() == len && starts_at(b, name, predefined[k]) { return true; }
+		k += 1;
+	}
+	false
+}
+
+const fn is_xml_char(c: u32) -> bool {
+	matches!(c, 0x9 | 0xA | 0xD | 0x20..=0xD7FF | 0xE000..=0xFFFD | 0x10000..=0x10FFFF)
+}
+
+// length of the entity or character reference starting with the `&` at `at`, 0 if it isn't a valid one
+const fn reference_len(b: &[u8], at: usize) -> usize {
+	let mut j = at + 1;
+	if j < b.len() && b[j] == b'#' {
+		j += 1;
+		let radix = if j < b.len() && b[j] == b'x' { j += 1; 16 } else { 10 };
+		let digits = j;
+		let mut value = 0u32;
+		while j < b.len() && b[j] != b';' {
+			let digit = match (b[j] as char).to_digit(radix) { Some(x) => x, None => return 0 };
+			value = match value.checked_mul(radix) { Some(x) => x.saturating_add(digit), None => u32::MAX };
+			j += 1;
+		}
+		if j == digits || !is_xml_char(value) { return 0; }
+	} else {
+		let len = name_len(b, j);
+		if len == 0 || !is_predefined_entity(b, j, len) { return 0; }
+		j += len;
+	}
+	if j < b.len() && b[j] == b';' { j + 1 - at } else { 0 }
+}
+
+// the attribute starting at `at` as its name's length and the index right after its value, `(0, 0)` if it's malformed
+const fn parse_attribute(b: &[u8], at: usize) -> (usize, usize) {
+	let len = name_len(b, at);
+	if len == 0 { return (0, 0); }
+	let mut j = skip_spaces(b, at + len);
+	if j >= b.len() || b[j] != b'=' { return (0, 0); }
+	j = skip_spaces(b, j + 1);
+	if j >= b.len() || (b[j] != b'"' && b[j] != b'\'') { return (0, 0); }
+	let quote = b[j];
+	j += 1;
+	while j < b.len() && b[j] != quote {
+		if b[j] == b'<' { return (0, 0); }
+		if b[j] == b'&' {
+			let len = reference_len(b, j);
+			if len == 0 { return (0, 0); }
+			j += len;
+		} else {
+			j += 1;
+		}
+	}
+	if j >= b.len() { return (0, 0); }
+	(len, j + 1)
+}
+
+// whether the attribute at `at` repeats one of those between `from` and it
+const fn is_duplicate_attribute(b: &[u8], from: usize, at: usize, len: usize) -> bool {
+	let mut k = skip_spaces(b, from);
+	while k < at {
+		let (other_len, next) = parse_attribute(b, k);
+		if other_len == len && same_bytes(b, k, at, len) { return true; }
+		k = skip_spaces(b, next);
+	}
+	false
+}
+
+/// Checks that `xml` parses the way `roxmltree` would have it, so `svgs!` can catch broken files at compile time:
+/// a single root element with properly nested and matching tags, attributes that are quoted, unique and free of `<`,
+/// references that are valid characters or predefined entities, declared namespace prefixes and no doctype.
+///
+/// It's not a full parser - elements nested deeper than 1024 levels are rejected, and a prefix declared anywhere in the file is accepted everywhere.
+#[doc(hidden)]
+pub const fn __is_well_formed(xml: &str) -> bool {
+	const MAX_DEPTH: usize = 1024;
+	let b = xml.as_bytes();
+	let mut stack = [(0usize, 0usize); MAX_DEPTH];
+	let mut depth = 0;
+	let mut roots = 0;
+	let mut i = if starts_at(b, 0, b"\xEF\xBB\xBF") { 3 } else { 0 };
+	while i < b.len() {
+		if b[i] == b'&' {
+			let len = reference_len(b, i);
+			if len == 0 || depth == 0 { return false; }
+			i += len;
+			continue;
+		}
+		if b[i] != b'<' {
+			if depth == 0 && !is_space(b[i]) { return false; }
+			i += 1;
+			continue;
+		}
+
+		// comments, cdata and processing instructions
+		let skipped = if starts_at(b, i, b"<!--") {
+			match find(b, i + 4, b"-->") { Some(x) => Some(x + 3), None => return false }
+		} else if starts_at(b, i, b"<![CDATA[") {
+			if depth == 0 { return false; }
+			match find(b, i + 9, b"]]>") { Some(x) => Some(x + 3), None => return false }
+		} else if starts_at(b, i, b"<?") {
+			match find(b, i + 2, b"?>") { Some(x) => Some(x + 2), None => return false }
+		} else if starts_at(b, i, b"<!") {
+			// including doctypes, which `roxmltree` rejects by default
+			return false;
+		} else {
+			None
+		};
+		if let Some(x) = skipped { i = x; continue; }
+
+		let closing = starts_at(b, i, b"</");
+		let name_start = i + 1 + closing as usize;
+		let name_len = name_len(b, name_start);
+		if name_len == 0 || !is_declared_prefix(b, name_start, name_len) { return false; }
+		let mut j = name_start + name_len;
+
+		if closing {
+			j = skip_spaces(b, j);
+			if j >= b.len() || b[j] != b'>' || depth == 0 { return false; }
+			depth -= 1;
+			let (open_start, open_len) = stack[depth];
+			if open_len != name_len || !same_bytes(b, open_start, name_start, name_len) { return false; }
+			i = j + 1;
+			continue;
+		}
+
+		let self_closing = loop {
+			let attribute = skip_spaces(b, j);
+			if attribute >= b.len() { return false; }
+			if b[attribute] == b'>' { j = attribute + 1; break false; }
+			if starts_at(b, attribute, b"/>") { j = attribute + 2; break true; }
+			// attributes have to be separated by whitespace
+			if attribute == j { return false; }
+			let (len, next) = parse_attribute(b, attribute);
+			if len == 0 || !is_declared_prefix(b, attribute, len) || is_duplicate_attribute(b, name_start + name_len, attribute, len) { return false; }
+			j = next;
+		};
+
+		if depth == 0 {
+			if roots != 0 { return false; }
+			roots += 1;
+		}
+		if !self_closing {
+			if depth == MAX_DEPTH { return false; }
+			stack[depth] = (name_start, name_len);
+			depth += 1;
+		}
+		i = j;
+	}
+	depth == 0 && roots == 1
+}
+
+/// Generates a function returning a fresh copy of each svg, e.g. `svgs!("../icons/", icon_home => "home.svg")` makes `icon_home()`.
+///
+/// Starting with `by_name = icon_by_name,` also makes `icon_by_name(&str)`, a lookup for icons only known at runtime.
+/// Files that aren't well-formed xml fail the build, see `__is_well_formed` for what's checked.
 #[macro_export]
 macro_rules! __svgs {
-	($base:expr, $($name:ident => $address:expr),*$(,)*) => {$(
+	(by_name = $by_name:ident, $base:expr, $($name:ident => $address:expr),*$(,)*) => {
+		$crate::__svgs!($base, $($name => $address),*);
+
+		/// Looks up one of the svgs by its function's name.
 		#[must_use]
-		pub fn $name() -> hobo::create::Svg {
-			thread_local! { static TEMPLATE: web_sys::SvgElement = $crate::svg::xml_to_svg(&roxmltree::Document::parse(include_str!(concat!($base, $address))).unwrap().root_element()) }
-			let element: web_sys::SvgElement = wasm_bindgen::JsCast::dyn_into(TEMPLATE.with(|x| x.clone_node_with_deep(true).unwrap())).unwrap();
-			hobo::create::Svg(hobo::create::svg_element(&element))
+		pub fn $by_name(name: &str) -> Option<hobo::create::Svg> {
+			match name {
+				$(stringify!($name) => Some($name()),)*
+				_ => None,
+			}
 		}
-	)*};
+	};
+	($base:expr, $($name:ident => $address:expr),*$(,)*) => {
+		$(
+			#[must_use]
+			pub fn $name() -> hobo::create::Svg {
+				const _: () = assert!($crate::svg::__is_well_formed(include_str!(concat!($base, $address))), concat!("malformed svg: ", $base, $address));
+				thread_local! { static TEMPLATE: web_sys::SvgElement = $crate::svg::__template(include_str!(concat!($base, $address)), concat!($base, $address)) }
+				let element: web_sys::SvgElement = wasm_bindgen::JsCast::dyn_into(TEMPLATE.with(|x| x.clone_node_with_deep(true).unwrap())).unwrap();
+				hobo::create::Svg(hobo::create::svg_element(&element))
+			}
+		)*
+	};
+}
+
+// anything `__is_well_formed` lets through that `roxmltree` still rejects becomes an empty svg rather than a panic
+#[doc(hidden)]
+pub fn __template(xml: &str, path: &str) -> web_sys::SvgElement {
+	match roxmltree::Document::parse(xml) {
+		Ok(document) => xml_to_svg(&document.root_element()),
+		Err(e) => {
+			log::error!("malformed svg {path}: {e}");
+			wasm_bindgen::JsCast::unchecked_into(document().create_element_ns(Some(wasm_bindgen::intern("http://www.w3.org/2000/svg")), "svg").unwrap())
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sanitized<'a>(document: &'a roxmltree::Document) -> Option<SanitizedNode<'a>> {
+		sanitize(&document.root_element(), &SvgAllowlist::default())
+	}
+
+	fn attributes<'a>(node: &'a SanitizedNode<'a>) -> &'a [(&'a str, &'a str)] {
+		match node { SanitizedNode::Element { attributes, .. } => attributes, SanitizedNode::Text(_) => &[] }
+	}
+
+	fn children<'a>(node: &'a SanitizedNode<'a>) -> &'a [SanitizedNode<'a>] {
+		match node { SanitizedNode::Element { children, .. } => children, SanitizedNode::Text(_) => &[] }
+	}
+
+	#[test]
+	fn drops_scripts() {
+		let document = roxmltree::Document::parse(r#"<svg xmlns="http://www.w3.org/2000/svg"><script>alert(1)</script><path d="M0 0"/></svg>"#).unwrap();
+		let svg = sanitized(&document).unwrap();
+		assert_eq!(children(&svg), [SanitizedNode::Element { name: "path", attributes: vec![("d", "M0 0")], children: vec![] }]);
+	}
+
+	#[test]
+	fn drops_event_handlers() {
+		let document = roxmltree::Document::parse(r#"<svg xmlns="http://www.w3.org/2000/svg" onload="alert(1)" ONCLICK="alert(2)" width="10"/>"#).unwrap();
+		let svg = sanitized(&document).unwrap();
+		assert_eq!(attributes(&svg), [("width", "10")]);
+	}
+
+	#[test]
+	fn drops_external_hrefs() {
+		let document = roxmltree::Document::parse(r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+			<use href="https://evil.example/x.svg#a"/>
+			<use xlink:href="javascript:alert(1)"/>
+			<use href="#local"/>
+		</svg>"##).unwrap();
+		let svg = sanitized(&document).unwrap();
+		let hrefs = children(&svg).iter().map(|x| attributes(x).to_vec()).filter(|x| !x.is_empty()).collect::<Vec<_>>();
+		assert_eq!(hrefs, [vec![("href", "#local")]]);
+	}
+
+	#[test]
+	fn drops_external_urls_in_any_case() {
+		let document = roxmltree::Document::parse(r##"<svg xmlns="http://www.w3.org/2000/svg">
+			<rect fill="URL(https://evil.example/x)" stroke="url(#gradient)" style="fill: Url('https://evil.example/y')" mask="uRl( #mask)"/>
+		</svg>"##).unwrap();
+		let svg = sanitized(&document).unwrap();
+		assert_eq!(attributes(&children(&svg)[1]), [("stroke", "url(#gradient)"), ("mask", "uRl( #mask)")]);
+	}
+
+	#[test]
+	fn rejects_disallowed_root() {
+		let document = roxmltree::Document::parse(r#"<script xmlns="http://www.w3.org/2000/svg">alert(1)</script>"#).unwrap();
+		assert_eq!(sanitized(&document), None);
+	}
+
+	// the validator is only useful if it agrees with the parser that's used at runtime
+	fn assert_matches_roxmltree(xml: &str, well_formed: bool) {
+		assert_eq!(roxmltree::Document::parse(xml).is_ok(), well_formed, "roxmltree disagrees about {xml}");
+		assert_eq!(__is_well_formed(xml), well_formed, "{xml}");
+	}
+
+	#[test]
+	fn validates_structure() {
+		assert_matches_roxmltree(r#"<svg xmlns="http://www.w3.org/2000/svg"><g><path d="M0 0"/></g></svg>"#, true);
+		assert_matches_roxmltree(r#"<?xml version="1.0"?><!-- icon --><svg><title>a > b</title><![CDATA[<x>]]></svg>"#, true);
+		assert_matches_roxmltree("<svg><g></svg></g>", false);
+		assert_matches_roxmltree("<svg><g></svg>", false);
+		assert_matches_roxmltree("<svg/><svg/>", false);
+		assert_matches_roxmltree("text<svg/>", false);
+		assert_matches_roxmltree("<svg></svg >", true);
+	}
+
+	#[test]
+	fn validates_attributes() {
+		assert_matches_roxmltree(r#"<svg a="1" b='>'/>"#, true);
+		assert_matches_roxmltree(r#"<svg a="1" a="2"/>"#, false);
+		assert_matches_roxmltree(r#"<svg a="<"/>"#, false);
+		assert_matches_roxmltree("<svg a=1/>", false);
+		assert_matches_roxmltree(r#"<svg a="1"b="2"/>"#, false);
+		assert_matches_roxmltree(r##"<svg xlink:href="#a"/>"##, false);
+		assert_matches_roxmltree(r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="#a"/></svg>"##, true);
+	}
+
+	#[test]
+	fn validates_references() {
+		assert_matches_roxmltree("<svg><text>a &amp; b &lt; &#65; &#x42;</text></svg>", true);
+		assert_matches_roxmltree("<svg><text>a & b</text></svg>", false);
+		assert_matches_roxmltree("<svg><text>&nbsp;</text></svg>", false);
+		assert_matches_roxmltree("<svg><text>&#0;</text></svg>", false);
+		assert_matches_roxmltree("<svg><text>&#xZZ;</text></svg>", false);
+		assert_matches_roxmltree(r#"<svg a="&amp"/>"#, false);
+		assert_matches_roxmltree(r#"<!DOCTYPE svg [ <!ENTITY width "10"> ]><svg width="&width;"/>"#, false);
+	}
+
+	#[test]
+	fn accepts_deep_nesting() {
+		// too deep for roxmltree to parse on a test thread's stack, so only the validator is checked
+		let xml = format!("<svg>{}{}</svg>", "<g>".repeat(500), "</g>".repeat(500));
+		assert!(__is_well_formed(&xml));
+	}
+
 }
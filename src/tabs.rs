@@ -0,0 +1,107 @@
+use hobo::{prelude::*, create as e, signal::SignalExt};
+use super::entity_ext::AsEntityExt;
+
+type ActiveTab = hobo::signal::Mutable<usize>;
+
+static NEXT_TABS_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// A single tab: the label for its button and a builder for its panel contents.
+pub struct Tab {
+	label: String,
+	panel: Box<dyn FnOnce() -> hobo::Element>,
+}
+
+impl Tab {
+	pub fn new<E: AsElement + 'static>(label: impl Into<String>, panel: impl FnOnce() -> E + 'static) -> Self {
+		Self { label: label.into(), panel: Box::new(move || panel().as_element()) }
+	}
+}
+
+struct TabsParts {
+	tablist: e::Div,
+	tabs: Vec<e::Button>,
+	panels: Vec<e::Div>,
+}
+
+/// Accessible tabs - a `role=tablist` of `role=tab` buttons each controlling a `role=tabpanel`.
+///
+/// Only the active tab is focusable (roving tabindex), ArrowLeft/ArrowRight/Home/End move between tabs while the tablist is focused.
+/// Panels are rendered lazily - each one is built the first time its tab gets activated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, AsElement)]
+pub struct Tabs(hobo::Element);
+
+impl Tabs {
+	pub fn new(tabs: impl IntoIterator<Item = Tab>, initial: usize) -> Self {
+		let id = NEXT_TABS_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+		let tabs_vec = tabs.into_iter().collect::<Vec<_>>();
+		let this = Self(e::div().as_element());
+		let active = ActiveTab::new(initial.min(tabs_vec.len().saturating_sub(1)));
+		let tab_count = tabs_vec.len();
+
+		let mut buttons = Vec::with_capacity(tab_count);
+		let mut panels = Vec::with_capacity(tab_count);
+		let mut builders = Vec::with_capacity(tab_count);
+		for (i, Tab { label, panel }) in tabs_vec.into_iter().enumerate() {
+			let tab_id = format!("tabs-{id}-tab-{i}");
+			let panel_id = format!("tabs-{id}-panel-{i}");
+			buttons.push(e::button()
+				.attr("role", "tab")
+				.id(&tab_id)
+				.attr("aria-controls", &panel_id)
+				.attr_signal("aria-selected", active.signal().map(move |x| if x == i { "true" } else { "false" }))
+				.attr_signal(web_str::tabindex(), active.signal().map(move |x| if x == i { "0" } else { "-1" }))
+				.text(label)
+				.on_click(move |_| this.set_active(i))
+			);
+			panels.push(e::div()
+				.attr("role", "tabpanel")
+				.id(&panel_id)
+				.attr("aria-labelledby", &tab_id)
+				.attr(web_str::tabindex(), "0")
+				.bool_attr_signal(web_str::hidden(), active.signal().map(move |x| x != i))
+			);
+			builders.push(Some(panel));
+		}
+
+		let tablist = e::div()
+			.attr("role", "tablist")
+			.children(buttons.iter().copied())
+			.on_key_down(move |e| {
+				if tab_count == 0 { return; }
+				let current = this.active();
+				let next = match e.key().as_str() {
+					"ArrowRight" => (current + 1) % tab_count,
+					"ArrowLeft" => (current + tab_count - 1) % tab_count,
+					"Home" => 0,
+					"End" => tab_count - 1,
+					_ => return,
+				};
+				e.prevent_default();
+				this.set_active(next);
+				this.tab(next).get_cmp::<web_sys::HtmlElement>().focus().ok();
+			});
+
+		this.add_bundle(active.signal().subscribe(move |i| {
+			let Some(build) = builders.get_mut(i).and_then(Option::take) else { return; };
+			let panel = this.panel(i);
+			panel.add_child(build());
+		}));
+
+		this
+			.child(tablist)
+			.children(panels.iter().copied())
+			.component(active)
+			.component(TabsParts { tablist, tabs: buttons, panels })
+	}
+
+	pub fn active(&self) -> usize { self.get_cmp::<ActiveTab>().get() }
+	pub fn set_active(&self, i: usize) {
+		if i >= self.get_cmp::<TabsParts>().tabs.len() { log::warn!("tab index out of bounds: {i}"); return; }
+		self.get_cmp::<ActiveTab>().set_neq(i);
+	}
+	pub fn active_signal(&self) -> impl hobo::signal::Signal<Item = usize> + 'static { self.get_cmp::<ActiveTab>().signal() }
+
+	pub fn tablist(&self) -> e::Div { self.get_cmp::<TabsParts>().tablist }
+	pub fn tab(&self, i: usize) -> e::Button { self.get_cmp::<TabsParts>().tabs[i] }
+	pub fn panel(&self, i: usize) -> e::Div { self.get_cmp::<TabsParts>().panels[i] }
+}
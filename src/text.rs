@@ -0,0 +1,70 @@
+use std::{cell::RefCell, collections::HashMap};
+use hobo::{prelude::*, create as e};
+use super::{document, window};
+
+/// Vertical metrics of a font, all in px.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FontMetrics {
+	/// Distance from the baseline to the top of the font's bounding box.
+	pub ascent: f64,
+	/// Distance from the baseline to the bottom of the font's bounding box.
+	pub descent: f64,
+	/// Extra space between lines on top of ascent + descent, if the line height is known.
+	pub line_gap: f64,
+	/// Height of capital letters above the baseline.
+	pub cap_height: f64,
+	/// Height of lowercase letters above the baseline.
+	pub x_height: f64,
+}
+
+thread_local! {
+	static CANVAS_CONTEXT: web_sys::CanvasRenderingContext2d = {
+		let canvas: web_sys::HtmlCanvasElement = document().create_element("canvas").unwrap().unchecked_into();
+		canvas.get_context("2d").unwrap().unwrap().unchecked_into()
+	};
+	static METRICS_CACHE: RefCell<HashMap<String, FontMetrics>> = RefCell::new(HashMap::new());
+}
+
+// canvas wants the `font` shorthand, which is easiest to get by resolving the style on an actual element
+fn resolve_font(font: &css::Style) -> Option<(String, Option<f64>)> {
+	let body = document().body()?;
+	let probe = e::span().class(font.clone()).text("x").allow_no_parent();
+	body.append_child(&probe.get_cmp::<web_sys::Node>()).ok()?;
+	let resolved = window().get_computed_style(&probe.get_cmp::<web_sys::Element>()).ok().flatten().map(|computed| {
+		let get = |name: &str| computed.get_property_value(name).unwrap_or_default();
+		let shorthand = format!("{} {} {} {} {}", get("font-style"), get("font-variant"), get("font-weight"), get("font-size"), get("font-family"));
+		let line_height = get("line-height").strip_suffix("px").and_then(|x| x.parse::<f64>().ok());
+		(shorthand, line_height)
+	});
+	probe.remove();
+	resolved
+}
+
+/// Measures ascent/descent/line gap/cap height/x height of a font, cached per resolved font.
+///
+/// Uses the canvas `measureText` font bounding box metrics where available,
+/// falling back to the actual bounding box of glyphs that reach the extremes.
+pub fn font_metrics(font: &css::Style) -> FontMetrics {
+	let Some((shorthand, line_height)) = resolve_font(font) else { return FontMetrics::default(); };
+	if let Some(metrics) = METRICS_CACHE.with(|x| x.borrow().get(&shorthand).copied()) { return metrics; }
+
+	let metrics = CANVAS_CONTEXT.with(|ctx| {
+		ctx.set_font(&shorthand);
+		let measure = |text: &str| ctx.measure_text(text).ok();
+		let extremes = measure("ÅHgjpqy|");
+		let font_ascent = extremes.as_ref().map(web_sys::TextMetrics::font_bounding_box_ascent).filter(|x| x.is_finite());
+		let font_descent = extremes.as_ref().map(web_sys::TextMetrics::font_bounding_box_descent).filter(|x| x.is_finite());
+		let ascent = font_ascent.or_else(|| extremes.as_ref().map(web_sys::TextMetrics::actual_bounding_box_ascent)).unwrap_or_default();
+		let descent = font_descent.or_else(|| extremes.as_ref().map(web_sys::TextMetrics::actual_bounding_box_descent)).unwrap_or_default();
+		FontMetrics {
+			ascent,
+			descent,
+			line_gap: line_height.map_or(0., |x| f64::max(0., x - ascent - descent)),
+			cap_height: measure("H").map_or(0., |x| x.actual_bounding_box_ascent()),
+			x_height: measure("x").map_or(0., |x| x.actual_bounding_box_ascent()),
+		}
+	});
+
+	METRICS_CACHE.with(|x| x.borrow_mut().insert(shorthand, metrics));
+	metrics
+}
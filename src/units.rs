@@ -0,0 +1,81 @@
+use hobo::prelude::*;
+use super::{document, window};
+use super::listener::{listen, EventListener};
+use super::observer::{observe_mutations, MutationObserverGuard};
+
+/// What `root_font_size` falls back to when there's no `<html>` to read it from, the browsers' default.
+pub const DEFAULT_ROOT_FONT_SIZE_PX: f64 = 16.;
+
+struct RootFontSize {
+	value: hobo::signal::Mutable<f64>,
+	_resize: EventListener,
+	_mutations: Option<MutationObserverGuard>,
+}
+
+thread_local! {
+	// tracked from first use on, for as long as the page lives
+	static ROOT_FONT_SIZE: RootFontSize = {
+		let value = hobo::signal::Mutable::new(read_root_font_size());
+		let resize = listen(&window(), "resize", #[clown::clown] move |_: web_sys::Event| honk!(value).set_neq(read_root_font_size()));
+		let mutations = document().document_element().map(|root| {
+			let value = value.clone();
+			let options = web_sys::MutationObserverInit::new();
+			options.set_attributes(true);
+			options.set_attribute_filter(&["style", "class"].into_iter().map(JsValue::from_str).collect::<js_sys::Array>());
+			observe_mutations(&root, &options, move |_| value.set_neq(read_root_font_size()))
+		});
+		RootFontSize { value, _resize: resize, _mutations: mutations }
+	};
+}
+
+fn read_root_font_size() -> f64 {
+	let Some(root) = document().document_element() else { return DEFAULT_ROOT_FONT_SIZE_PX; };
+	window().get_computed_style(&root).ok().flatten()
+		.and_then(|x| x.get_property_value("font-size").ok())
+		.and_then(|x| x.strip_suffix("px")?.parse::<f64>().ok())
+		.unwrap_or(DEFAULT_ROOT_FONT_SIZE_PX)
+}
+
+fn viewport_size() -> (f64, f64) {
+	let window = window();
+	let dimension = |x: Result<JsValue, JsValue>| x.ok().and_then(|x| x.as_f64()).unwrap_or_default();
+	(dimension(window.inner_width()), dimension(window.inner_height()))
+}
+
+/// Computed `font-size` of `<html>` in px, i.e. what `1rem` is.
+pub fn root_font_size() -> f64 { ROOT_FONT_SIZE.with(|x| x.value.get()) }
+
+/// Signal of `root_font_size`, starting with the current one.
+///
+/// Re-checked on window resize (which includes browser zoom) and when `<html>`'s `style` or `class` changes.
+/// A stylesheet being added or removed isn't noticed until one of those happens.
+pub fn root_font_size_signal() -> impl hobo::signal::Signal<Item = f64> + 'static { ROOT_FONT_SIZE.with(|x| x.value.signal()) }
+
+/// Converts a CSS length to px given the current root font size and viewport.
+///
+/// There's no element to go on, so this assumes the context of the root element:
+/// * `em` is the same as `rem`
+/// * `%` is a percentage of the viewport width - right for e.g. the `width` of something fixed-position,
+///   but wrong for vertical properties or anything laid out in a smaller container, use `resolve_length_relative` for those
+///
+/// `fr` and durations aren't lengths and resolve to `0.`.
+pub fn resolve_length(unit: &css::Unit) -> f64 { resolve_length_relative(unit, viewport_size().0) }
+
+/// Same as `resolve_length`, but `%` is a percentage of `percent_base` px.
+pub fn resolve_length_relative(unit: &css::Unit, percent_base: f64) -> f64 {
+	let (viewport_width, viewport_height) = viewport_size();
+	let resolve = |unit: &css::Unit| resolve_length_relative(unit, percent_base);
+	match unit {
+		css::Unit::Zero => 0.,
+		css::Unit::Px(x) => f64::from(x.into_inner()),
+		css::Unit::Em(x) | css::Unit::Rem(x) => f64::from(x.into_inner()) * root_font_size(),
+		css::Unit::Vw(x) => f64::from(x.into_inner()) * viewport_width / 100.,
+		css::Unit::Vh(x) => f64::from(x.into_inner()) * viewport_height / 100.,
+		css::Unit::Vmin(x) => f64::from(x.into_inner()) * f64::min(viewport_width, viewport_height) / 100.,
+		css::Unit::Vmax(x) => f64::from(x.into_inner()) * f64::max(viewport_width, viewport_height) / 100.,
+		css::Unit::Percent(x) => f64::from(x.into_inner()) * percent_base / 100.,
+		css::Unit::Calc(left, css::units::Operator::Plus, right) => resolve(left) + resolve(right),
+		css::Unit::Calc(left, css::units::Operator::Minus, right) => resolve(left) - resolve(right),
+		css::Unit::Fr(_) | css::Unit::Duration(_) => { log::warn!("can't resolve {unit} to a length"); 0. },
+	}
+}